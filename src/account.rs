@@ -0,0 +1,110 @@
+use crate::puzzle::decode_entities;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::BTreeMap;
+
+lazy_static! {
+    static ref USER_RE: Regex = Regex::new(r#"<div class="user">([^<]*)"#).unwrap();
+    static ref EVENT_RE: Regex = Regex::new(
+        r#"<div class="eventlist-event"><a href="/(\d+)">[^<]*</a>\s*(?:<span class="star-count">(\d+)\*</span>)?"#
+    )
+    .unwrap();
+}
+
+/// The logged-in account as shown on the `/{year}/events` page.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Account {
+    pub name: String,
+    /// Stars by event, including events without any.
+    pub stars: BTreeMap<i32, u32>,
+}
+
+impl Account {
+    /// Parses the events page, or returns `None` if it was served to a
+    /// visitor who is not logged in.
+    pub fn parse(html: &str) -> Option<Account> {
+        let name = decode_entities(USER_RE.captures(html)?[1].trim());
+        let stars = EVENT_RE
+            .captures_iter(html)
+            .filter_map(|caps| {
+                let year = caps[1].parse().ok()?;
+                let stars = caps
+                    .get(2)
+                    .map_or(Some(0), |stars| stars.as_str().parse().ok())?;
+                Some((year, stars))
+            })
+            .collect();
+        Some(Account { name, stars })
+    }
+
+    pub fn total_stars(&self) -> u32 {
+        self.stars.values().sum()
+    }
+
+    /// Lists the stars of each event, most recent first.
+    pub fn render(&self) -> String {
+        let mut lines = vec![format!("logged in as {}", self.name)];
+        lines.extend(
+            self.stars
+                .iter()
+                .rev()
+                .map(|(year, stars)| format!("{}  {:>2}*", year, stars)),
+        );
+        lines.push(format!("total {:>2}*", self.total_stars()));
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    pub const EVENTS: &str = r#"<!DOCTYPE html>
+<html lang="en-us">
+<body>
+<header><div><h1 class="title-global"><a href="/">Advent of Code</a></h1><div class="user">Nicholas &amp; Co <a href="/2023/support" class="supporter-badge" title="Advent of Code Supporter">(AoC++)</a> <span class="star-count">50*</span></div></div></header>
+<main>
+<article><p>Here are all of the past events:</p>
+<div class="eventlist-event"><a href="/2023">[2023]</a> <span class="star-count">50*</span></div>
+<div class="eventlist-event"><a href="/2022">[2022]</a> <span class="star-count">7*</span></div>
+<div class="eventlist-event"><a href="/2015">[2015]</a></div>
+</article>
+</main>
+</body>
+</html>"#;
+
+    pub const LOGGED_OUT: &str = r#"<!DOCTYPE html>
+<html lang="en-us">
+<body>
+<header><div><h1 class="title-global"><a href="/">Advent of Code</a></h1><div><a href="/2023/auth/login">[Log In]</a></div></div></header>
+<main>
+<article><p>Here are all of the past events:</p>
+<div class="eventlist-event"><a href="/2023">[2023]</a></div>
+</article>
+</main>
+</body>
+</html>"#;
+
+    #[test]
+    fn parse_events() {
+        let account = Account::parse(EVENTS).unwrap();
+        assert_eq!(account.name, "Nicholas & Co");
+        assert_eq!(
+            account.stars.into_iter().collect::<Vec<_>>(),
+            vec![(2015, 0), (2022, 7), (2023, 50)]
+        );
+        assert_eq!(Account::parse(LOGGED_OUT), None);
+    }
+
+    #[test]
+    fn render() {
+        assert_eq!(
+            Account::parse(EVENTS).unwrap().render(),
+            "logged in as Nicholas & Co
+2023  50*
+2022   7*
+2015   0*
+total 57*"
+        );
+    }
+}
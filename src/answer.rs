@@ -0,0 +1,246 @@
+use std::fmt::{self, Display, Formatter};
+
+/// Letters of the 4x6 pixel font used by puzzles that draw their answers,
+/// keyed by their rows.
+const FONT: &[(char, [&str; 6])] = &[
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// A puzzle answer, classified from the text a solution produced.
+#[derive(Clone, Debug)]
+pub enum Answer {
+    /// An integer in canonical form, so that no leading zeros or signs are
+    /// lost when it is submitted again.
+    Integer(i128),
+    Text(String),
+    /// A multi-line drawing normalized to rows of `#` and `.`.
+    Art(Vec<String>),
+}
+
+impl Answer {
+    /// Classifies an answer, ignoring surrounding whitespace. Drawings span
+    /// several lines or, when only one row high, start on a line of their
+    /// own.
+    pub fn new(answer: &str) -> Answer {
+        let trimmed = answer.trim();
+        let drawn = answer.trim_start_matches(' ').starts_with('\n')
+            && trimmed.chars().all(|c| matches!(c, '#' | '.' | '█' | ' '));
+        if trimmed.contains('\n') || drawn {
+            return Answer::Art(normalize_art(answer));
+        }
+        match trimmed.parse::<i128>() {
+            Ok(n) if n.to_string() == trimmed => Answer::Integer(n),
+            _ => Answer::Text(trimmed.to_owned()),
+        }
+    }
+
+    /// Reads the letters drawn by an art answer, if they are all known.
+    pub fn ocr(&self) -> Option<String> {
+        let rows = match self {
+            Answer::Art(rows) if rows.len() == 6 => rows,
+            _ => return None,
+        };
+        let width = rows.iter().map(String::len).max()?;
+        (0..width)
+            .step_by(5)
+            .map(|x| {
+                let glyph = rows
+                    .iter()
+                    .map(|row| format!("{:.<4}", row.get(x..).unwrap_or("")))
+                    .map(|row| row[..4].to_string())
+                    .collect::<Vec<_>>();
+                FONT.iter()
+                    .find(|(_, rows)| rows.iter().zip(&glyph).all(|(a, b)| a == b))
+                    .map(|&(letter, _)| letter)
+            })
+            .collect()
+    }
+
+    /// The text to submit, which for art is the letters it draws.
+    pub fn submission(&self) -> Option<String> {
+        match self {
+            Answer::Art(_) => self.ocr(),
+            answer => Some(answer.to_string()),
+        }
+    }
+
+    /// Whether this looks like an unimplemented stub returning 0.
+    pub fn is_stub(&self) -> bool {
+        matches!(self, Answer::Integer(0))
+    }
+}
+
+/// Maps pixels to `#` and `.`, dropping blank rows around the drawing and
+/// trailing off pixels, but keeping leading ones that align the letters.
+fn normalize_art(art: &str) -> Vec<String> {
+    let rows = art.lines().collect::<Vec<_>>();
+    let first = rows.iter().position(|row| !row.trim().is_empty());
+    let last = rows.iter().rposition(|row| !row.trim().is_empty());
+    let rows = match (first, last) {
+        (Some(first), Some(last)) => &rows[first..=last],
+        _ => &[],
+    };
+    rows.iter()
+        .map(|line| {
+            line.trim_end()
+                .chars()
+                .map(|c| match c {
+                    '#' | '█' => '#',
+                    _ => '.',
+                })
+                .collect::<String>()
+                .trim_end_matches('.')
+                .to_string()
+        })
+        .collect()
+}
+
+/// A drawing returned by a solution, kept as art however few rows it has.
+pub struct Art(pub String);
+
+impl From<Art> for Answer {
+    fn from(art: Art) -> Answer {
+        Answer::Art(normalize_art(&art.0))
+    }
+}
+
+impl From<String> for Answer {
+    fn from(answer: String) -> Answer {
+        Answer::Text(answer)
+    }
+}
+
+macro_rules! from_integers {
+    ($($int:ty),*) => {
+        $(
+            impl From<$int> for Answer {
+                fn from(answer: $int) -> Answer {
+                    Answer::Integer(answer as i128)
+                }
+            }
+        )*
+    };
+}
+
+from_integers!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, usize);
+
+/// Answers of different kinds are equal when they would be submitted the
+/// same way, so a drawing matches the letters recorded for it.
+impl PartialEq for Answer {
+    fn eq(&self, other: &Answer) -> bool {
+        match (self, other) {
+            (Answer::Integer(a), Answer::Integer(b)) => a == b,
+            (Answer::Text(a), Answer::Text(b)) => a == b,
+            (Answer::Art(a), Answer::Art(b)) => a == b,
+            (Answer::Art(_), Answer::Text(text)) | (Answer::Text(text), Answer::Art(_)) => {
+                self.ocr().or_else(|| other.ocr()).as_ref() == Some(text)
+            }
+            (Answer::Integer(n), Answer::Text(text)) | (Answer::Text(text), Answer::Integer(n)) => {
+                n.to_string() == *text
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Art is shown as the letters it draws, or as the drawing itself on its own
+/// lines when it cannot be read.
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Answer::Integer(n) => write!(f, "{}", n),
+            Answer::Text(text) => write!(f, "{}", text),
+            Answer::Art(rows) => match self.ocr() {
+                Some(letters) => write!(f, "{}", letters),
+                None => rows.iter().try_for_each(|row| write!(f, "\n{}", row)),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// The letters `AC` as drawn by puzzles.
+    pub const ART: &str = "
+.##...##.
+#..#.#..#
+#..#.#...
+####.#...
+#..#.#..#
+#..#..##.
+";
+
+    #[test]
+    fn classify() {
+        assert_eq!(Answer::new(" 42\n").to_string(), "42");
+        assert!(matches!(Answer::new("42"), Answer::Integer(42)));
+        assert!(matches!(Answer::new("-7"), Answer::Integer(-7)));
+        assert!(matches!(Answer::new("0042"), Answer::Text(_)));
+        assert!(matches!(Answer::new("05ace8e3"), Answer::Text(_)));
+        assert!(matches!(Answer::new(ART), Answer::Art(_)));
+        assert!(Answer::new("0").is_stub());
+    }
+
+    #[test]
+    fn ocr_letters() {
+        let art = Answer::new(ART);
+        assert_eq!(art.ocr().as_deref(), Some("AC"));
+        assert_eq!(art.to_string(), "AC");
+        assert_eq!(art.submission().as_deref(), Some("AC"));
+        assert_eq!(Answer::new("AC"), art);
+    }
+
+    #[test]
+    fn unreadable_art() {
+        let art = Answer::new(&ART.replace(".##...##.", "##...##.."));
+        assert_eq!(art.ocr(), None);
+        assert_eq!(art.submission(), None);
+        assert!(art.to_string().starts_with("\n##...##"));
+        assert_eq!(Answer::new(&art.to_string()), art);
+        assert_eq!(
+            Answer::new(&art.to_string().replace('#', "█").replace('.', " ")),
+            art
+        );
+    }
+
+    #[test]
+    fn single_row_art() {
+        assert!(matches!(Answer::new("\n#####"), Answer::Art(_)));
+        assert!(matches!(Answer::new("\n42"), Answer::Integer(42)));
+        assert!(matches!(Answer::new("#####"), Answer::Text(_)));
+        assert!(matches!(Answer::from(Art("#####".into())), Answer::Art(_)));
+    }
+
+    #[test]
+    fn conversions() {
+        assert!(matches!(Answer::from(42u8), Answer::Integer(42)));
+        assert!(matches!(Answer::from(-7i64), Answer::Integer(-7)));
+        assert!(matches!(Answer::from("142".to_string()), Answer::Text(_)));
+        assert_eq!(Answer::from("142".to_string()), Answer::new("142"));
+        assert_ne!(Answer::from("0142".to_string()), Answer::new("142"));
+    }
+
+    #[test]
+    fn compare() {
+        assert_eq!(Answer::new("12"), Answer::new(" 12 "));
+        assert_ne!(Answer::new("12"), Answer::new("012"));
+        assert_ne!(Answer::new("abc"), Answer::new("abd"));
+    }
+}
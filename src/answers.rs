@@ -0,0 +1,225 @@
+use crate::answer::Answer;
+use colored::{ColoredString, Colorize};
+use failure::Error;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+pub const AOC_ANSWERS_DIR_ENV: &str = "AOC_ANSWERS_DIR";
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Recorded {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part1: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part2: Option<String>,
+}
+
+impl Recorded {
+    fn part(&mut self, part: u8) -> &mut Option<String> {
+        match part {
+            1 => &mut self.part1,
+            2 => &mut self.part2,
+            _ => panic!("invalid puzzle part {}", part),
+        }
+    }
+}
+
+type Year = BTreeMap<String, Recorded>;
+
+/// Expected answers for our real inputs, stored per year in
+/// `{dir}/{year}.toml` with one `[dayNN]` table per day.
+#[derive(Debug)]
+pub struct Answers {
+    dir: PathBuf,
+    years: BTreeMap<i32, Year>,
+    dirty: BTreeSet<i32>,
+}
+
+impl Answers {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Answers {
+        Answers {
+            dir: dir.into(),
+            years: BTreeMap::new(),
+            dirty: BTreeSet::new(),
+        }
+    }
+
+    /// Uses `$AOC_ANSWERS_DIR` if set, then the `configured` directory, and
+    /// `answers` otherwise.
+    pub fn from_env(configured: Option<&Path>) -> Answers {
+        let dir = env::var_os(AOC_ANSWERS_DIR_ENV)
+            .map(PathBuf::from)
+            .or_else(|| configured.map(Path::to_owned))
+            .unwrap_or_else(|| PathBuf::from("answers"));
+        Answers::new(dir)
+    }
+
+    fn path(&self, year: i32) -> PathBuf {
+        self.dir.join(format!("{}.toml", year))
+    }
+
+    fn year(&mut self, year: i32) -> Result<&mut Year, Error> {
+        if !self.years.contains_key(&year) {
+            let path = self.path(year);
+            let answers = match fs::read_to_string(&path) {
+                Ok(contents) => toml::from_str(&contents)
+                    .map_err(|err| AnswersError::Parse(path.display().to_string(), err))?,
+                Err(err) if err.kind() == ErrorKind::NotFound => Year::new(),
+                Err(err) => return Err(AnswersError::Read(path.display().to_string(), err).into()),
+            };
+            self.years.insert(year, answers);
+        }
+        Ok(self.years.get_mut(&year).unwrap())
+    }
+
+    pub fn expected(&mut self, year: i32, day: i32, part: u8) -> Result<Option<String>, Error> {
+        Ok(self
+            .year(year)?
+            .get(&key(day))
+            .and_then(|recorded| match part {
+                1 => recorded.part1.clone(),
+                _ => recorded.part2.clone(),
+            }))
+    }
+
+    /// Records an answer, returning whether it changed the file. Existing
+    /// answers are only replaced when `overwrite` is set.
+    pub fn record(
+        &mut self,
+        year: i32,
+        day: i32,
+        part: u8,
+        answer: &str,
+        overwrite: bool,
+    ) -> Result<bool, Error> {
+        let slot = self.year(year)?.entry(key(day)).or_default().part(part);
+        if slot.as_deref() == Some(answer) || (slot.is_some() && !overwrite) {
+            return Ok(false);
+        }
+
+        *slot = Some(answer.to_owned());
+        self.dirty.insert(year);
+        Ok(true)
+    }
+
+    /// Writes back all years with newly recorded answers.
+    pub fn save(&mut self) -> Result<(), Error> {
+        if self.dirty.is_empty() {
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.dir)?;
+        for year in &self.dirty {
+            let contents = toml::to_string(&self.years[year])?;
+            fs::write(self.path(*year), contents)?;
+        }
+        self.dirty.clear();
+        Ok(())
+    }
+}
+
+fn key(day: i32) -> String {
+    format!("day{:02}", day)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Check {
+    Match,
+    Mismatch(String),
+    Unrecorded,
+}
+
+impl Check {
+    pub fn new(expected: Option<&str>, actual: &Answer) -> Check {
+        match expected {
+            Some(expected) if Answer::new(expected) == *actual => Check::Match,
+            Some(expected) => Check::Mismatch(expected.to_owned()),
+            None => Check::Unrecorded,
+        }
+    }
+
+    /// Whether the answer matched, or `None` if nothing was recorded.
+    pub fn matched(&self) -> Option<bool> {
+        match self {
+            Check::Match => Some(true),
+            Check::Mismatch(_) => Some(false),
+            Check::Unrecorded => None,
+        }
+    }
+
+    pub fn colored(&self) -> ColoredString {
+        match self {
+            Check::Match => "ok".green(),
+            Check::Mismatch(expected) => format!("MISMATCH, expected {}", expected).red(),
+            Check::Unrecorded => "unrecorded".yellow(),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AnswersError {
+    #[error("failed to read recorded answers {0}: {1}")]
+    Read(String, std::io::Error),
+    #[error("invalid recorded answers {0}: {1}")]
+    Parse(String, toml::de::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::tests::ART;
+    use crate::cache::tests::temp_dir;
+
+    #[test]
+    fn record_and_reload() {
+        let mut answers = Answers::new(temp_dir());
+        assert_eq!(answers.expected(2023, 1, 1).unwrap(), None);
+
+        assert!(answers.record(2023, 1, 1, "142", false).unwrap());
+        assert!(answers.record(2023, 10, 2, "\n##\n..", false).unwrap());
+        assert!(!answers.record(2023, 1, 1, "143", false).unwrap());
+        answers.save().unwrap();
+
+        let mut reloaded = Answers::new(&answers.dir);
+        assert_eq!(
+            reloaded.expected(2023, 1, 1).unwrap().as_deref(),
+            Some("142")
+        );
+        assert_eq!(reloaded.expected(2023, 1, 2).unwrap(), None);
+        assert_eq!(
+            reloaded.expected(2023, 10, 2).unwrap().as_deref(),
+            Some("\n##\n..")
+        );
+
+        assert!(reloaded.record(2023, 1, 1, "143", true).unwrap());
+        assert_eq!(
+            reloaded.expected(2023, 1, 1).unwrap().as_deref(),
+            Some("143")
+        );
+    }
+
+    #[test]
+    fn checks() {
+        let answer = |answer| Answer::new(answer);
+        assert_eq!(Check::new(Some("1"), &answer("1")), Check::Match);
+        assert_eq!(
+            Check::new(Some("1"), &answer("2")),
+            Check::Mismatch("1".to_owned())
+        );
+        assert_eq!(Check::new(None, &answer("2")), Check::Unrecorded);
+        assert_eq!(Check::new(Some("AC"), &answer(ART)), Check::Match);
+    }
+
+    #[test]
+    fn malformed_file() {
+        let dir = temp_dir();
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("2023.toml"), "[day01\npart1 = 1").unwrap();
+        let err = Answers::new(&dir).expected(2023, 1, 1).unwrap_err();
+        assert!(err.to_string().starts_with("invalid recorded answers "));
+    }
+}
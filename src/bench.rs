@@ -0,0 +1,184 @@
+use crate::solution::Parser;
+use serde_json::{json, Value};
+use std::fmt::{self, Display, Formatter};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// How long to keep benchmarking each phase of a solution.
+#[derive(Clone, Copy, Debug)]
+pub enum Budget {
+    Runs(usize),
+    Time(Duration),
+}
+
+impl Budget {
+    fn measure<F: FnMut()>(self, mut f: F) -> Vec<Duration> {
+        // Warm up caches and lazily initialized statics before measuring.
+        f();
+
+        let mut samples = Vec::new();
+        let start = Instant::now();
+        loop {
+            let run = Instant::now();
+            f();
+            samples.push(run.elapsed());
+
+            let done = match self {
+                Budget::Runs(runs) => samples.len() >= runs,
+                Budget::Time(time) => start.elapsed() >= time,
+            };
+            if done {
+                return samples;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(samples: &[Duration]) -> Stats {
+        assert!(!samples.is_empty(), "no benchmark samples");
+
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let runs = sorted.len();
+        let median = if runs.is_multiple_of(2) {
+            (sorted[runs / 2 - 1] + sorted[runs / 2]) / 2
+        } else {
+            sorted[runs / 2]
+        };
+
+        let secs = sorted.iter().map(Duration::as_secs_f64).collect::<Vec<_>>();
+        let mean = secs.iter().sum::<f64>() / runs as f64;
+        let variance = secs.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / runs as f64;
+
+        Stats {
+            runs,
+            min: sorted[0],
+            median,
+            mean: Duration::from_secs_f64(mean),
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+
+    pub fn to_json(self) -> Value {
+        json!({
+            "runs": self.runs,
+            "min_ns": self.min.as_nanos() as u64,
+            "median_ns": self.median.as_nanos() as u64,
+            "mean_ns": self.mean.as_nanos() as u64,
+            "stddev_ns": self.stddev.as_nanos() as u64,
+        })
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "min {:>10.3?}  median {:>10.3?}  mean {:>10.3?}  stddev {:>10.3?}  ({} runs)",
+            self.min, self.median, self.mean, self.stddev, self.runs
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Report {
+    pub year: i32,
+    pub day: i32,
+    pub parse: Stats,
+    pub part1: Stats,
+    pub part2: Stats,
+}
+
+impl Report {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "year": self.year,
+            "day": self.day,
+            "parse": self.parse.to_json(),
+            "part1": self.part1.to_json(),
+            "part2": self.part2.to_json(),
+        })
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "Day {}", self.day)?;
+        writeln!(f, "  parse:    {}", self.parse)?;
+        writeln!(f, "  puzzle 1: {}", self.part1)?;
+        write!(f, "  puzzle 2: {}", self.part2)
+    }
+}
+
+/// Benchmarks parsing and both parts of a solution separately.
+pub fn bench(year: i32, day: i32, parse: Parser, input: &str, budget: Budget) -> Report {
+    let parse_samples = budget.measure(|| {
+        black_box(parse(black_box(input)));
+    });
+
+    let parsed = parse(input);
+    let part1_samples = budget.measure(|| {
+        black_box(parsed.part1());
+    });
+    let part2_samples = budget.measure(|| {
+        black_box(parsed.part2());
+    });
+
+    Report {
+        year,
+        day,
+        parse: Stats::from_samples(&parse_samples),
+        part1: Stats::from_samples(&part1_samples),
+        part2: Stats::from_samples(&part2_samples),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn stats() {
+        let stats = Stats::from_samples(&[ms(4), ms(2), ms(6), ms(8)]);
+        assert_eq!(stats.runs, 4);
+        assert_eq!(stats.min, ms(2));
+        assert_eq!(stats.median, ms(5));
+        assert_eq!(stats.mean, ms(5));
+        assert_eq!(stats.stddev.as_micros(), 2236);
+    }
+
+    #[test]
+    fn stats_odd_median() {
+        let stats = Stats::from_samples(&[ms(9), ms(1), ms(3)]);
+        assert_eq!(stats.median, ms(3));
+    }
+
+    #[test]
+    fn bench_runs() {
+        let report = bench(
+            2023,
+            14,
+            crate::registry().get(2023, 14).unwrap(),
+            "O.#\n.O.\n...",
+            Budget::Runs(3),
+        );
+        assert_eq!(report.parse.runs, 3);
+        assert_eq!(report.part1.runs, 3);
+        assert_eq!(report.part2.runs, 3);
+        assert_eq!(report.to_json()["part1"]["runs"], 3);
+    }
+}
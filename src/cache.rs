@@ -0,0 +1,158 @@
+use failure::Error;
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub const AOC_CACHE_DIR_ENV: &str = "AOC_CACHE_DIR";
+
+/// On-disk cache of puzzle inputs and descriptions laid out as
+/// `{dir}/{year}/{day}.txt` and `{dir}/{year}/{day}.html`, and of private
+/// leaderboards as `{dir}/{year}/leaderboard-{id}.json`.
+#[derive(Clone, Debug)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Cache {
+        Cache { dir: dir.into() }
+    }
+
+    /// Uses `$AOC_CACHE_DIR` if set, then the `configured` directory, and
+    /// `$XDG_CACHE_HOME/aoc` or `~/.cache/aoc` otherwise.
+    pub fn from_env(configured: Option<&Path>) -> Cache {
+        let dir = env::var_os(AOC_CACHE_DIR_ENV)
+            .map(PathBuf::from)
+            .or_else(|| configured.map(Path::to_owned))
+            .or_else(|| env::var_os("XDG_CACHE_HOME").map(|dir| Path::new(&dir).join("aoc")))
+            .or_else(|| env::var_os("HOME").map(|dir| Path::new(&dir).join(".cache/aoc")))
+            .unwrap_or_else(|| env::temp_dir().join("aoc"));
+        Cache::new(dir)
+    }
+
+    pub fn input_path(&self, year: i32, day: i32) -> PathBuf {
+        self.dir.join(year.to_string()).join(format!("{}.txt", day))
+    }
+
+    pub fn read_input(&self, year: i32, day: i32) -> Result<Option<String>, Error> {
+        read(&self.input_path(year, day))
+    }
+
+    pub fn write_input(&self, year: i32, day: i32, input: &str) -> Result<(), Error> {
+        write(&self.input_path(year, day), input)
+    }
+
+    pub fn puzzle_path(&self, year: i32, day: i32) -> PathBuf {
+        self.dir
+            .join(year.to_string())
+            .join(format!("{}.html", day))
+    }
+
+    pub fn read_puzzle(&self, year: i32, day: i32) -> Result<Option<String>, Error> {
+        read(&self.puzzle_path(year, day))
+    }
+
+    pub fn puzzle_modified(&self, year: i32, day: i32) -> Result<Option<SystemTime>, Error> {
+        modified(&self.puzzle_path(year, day))
+    }
+
+    pub fn write_puzzle(&self, year: i32, day: i32, html: &str) -> Result<(), Error> {
+        write(&self.puzzle_path(year, day), html)
+    }
+
+    pub fn leaderboard_path(&self, year: i32, id: u64) -> PathBuf {
+        self.dir
+            .join(year.to_string())
+            .join(format!("leaderboard-{}.json", id))
+    }
+
+    /// Reads a cached leaderboard along with when it was fetched.
+    pub fn read_leaderboard(
+        &self,
+        year: i32,
+        id: u64,
+    ) -> Result<Option<(String, SystemTime)>, Error> {
+        let path = self.leaderboard_path(year, id);
+        match (read(&path)?, modified(&path)?) {
+            (Some(json), Some(modified)) => Ok(Some((json, modified))),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn write_leaderboard(&self, year: i32, id: u64, json: &str) -> Result<(), Error> {
+        write(&self.leaderboard_path(year, id), json)
+    }
+}
+
+fn read(path: &Path) -> Result<Option<String>, Error> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn modified(path: &Path) -> Result<Option<SystemTime>, Error> {
+    match fs::metadata(path) {
+        Ok(metadata) => Ok(Some(metadata.modified()?)),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), Error> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+#[derive(Debug, thiserror::Error)]
+#[allow(clippy::enum_variant_names)]
+pub enum CacheError {
+    #[error("no cached input for {year} day {day} at {path} (run without --offline to fetch it)")]
+    MissingInput { year: i32, day: i32, path: String },
+    #[error("no cached puzzle for {year} day {day} at {path} (run without --offline to fetch it)")]
+    MissingPuzzle { year: i32, day: i32, path: String },
+    #[error("no cached leaderboard {id} for {year} at {path} (run without --offline to fetch it)")]
+    MissingLeaderboard { year: i32, id: u64, path: String },
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::Cache;
+    use std::env;
+    use std::path::PathBuf;
+    use std::process;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Returns a fresh temporary directory path.
+    pub fn temp_dir() -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        env::temp_dir().join(format!(
+            "aoc-test-{}-{}",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ))
+    }
+
+    pub fn temp_cache() -> Cache {
+        Cache::new(temp_dir())
+    }
+
+    #[test]
+    fn input_roundtrip() {
+        let cache = temp_cache();
+        assert_eq!(cache.read_input(2023, 5).unwrap(), None);
+
+        cache.write_input(2023, 5, "1 2 3\n").unwrap();
+        assert_eq!(
+            cache.read_input(2023, 5).unwrap().as_deref(),
+            Some("1 2 3\n")
+        );
+        assert!(cache.input_path(2023, 5).ends_with("2023/5.txt"));
+    }
+}
@@ -0,0 +1,171 @@
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+
+/// Puzzles unlock at midnight US Eastern time, which is always UTC-5 in
+/// December.
+const UNLOCK_HOUR_UTC: u32 = 5;
+
+pub const FIRST_YEAR: i32 = 2015;
+
+/// Events ran for 25 days until they were shortened to 12 in 2025.
+pub fn days_in_event(year: i32) -> i32 {
+    if year >= 2025 {
+        12
+    } else {
+        25
+    }
+}
+
+pub fn unlock_time(year: i32, day: i32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(year, 12, day as u32, UNLOCK_HOUR_UTC, 0, 0)
+        .single()
+        .unwrap_or_else(|| panic!("invalid puzzle date {} day {}", year, day))
+}
+
+/// The year of the running event during December, or of the last one.
+pub fn default_year(now: DateTime<Utc>) -> i32 {
+    let eastern = now - Duration::hours(i64::from(UNLOCK_HOUR_UTC));
+    if eastern.month() == 12 {
+        eastern.year()
+    } else {
+        eastern.year() - 1
+    }
+}
+
+/// The year of the running event during December, or of the coming one, for
+/// waiting on a puzzle that has not unlocked yet.
+pub fn upcoming_year(now: DateTime<Utc>) -> i32 {
+    (now - Duration::hours(i64::from(UNLOCK_HOUR_UTC))).year()
+}
+
+/// The most recently unlocked day of an event.
+pub fn latest_day(year: i32, now: DateTime<Utc>) -> Result<i32, CalendarError> {
+    (1..=days_in_event(year))
+        .rev()
+        .find(|&day| unlock_time(year, day) <= now)
+        .ok_or(CalendarError::NothingUnlocked(year))
+}
+
+/// Parses a day argument, where `today` stands for the latest unlocked day.
+pub fn parse_day(year: i32, day: &str, now: DateTime<Utc>) -> Result<i32, CalendarError> {
+    if day == "today" {
+        return latest_day(year, now);
+    }
+    let day = day
+        .parse()
+        .map_err(|_| CalendarError::InvalidDay(day.to_owned()))?;
+    if day < 1 || day > days_in_event(year) {
+        return Err(CalendarError::NoSuchDay { year, day });
+    }
+    Ok(day)
+}
+
+/// Checks that a puzzle exists and has unlocked, so that it can be fetched.
+pub fn check_unlocked(year: i32, day: i32, now: DateTime<Utc>) -> Result<(), CalendarError> {
+    if year < FIRST_YEAR || day < 1 || day > days_in_event(year) {
+        return Err(CalendarError::NoSuchDay { year, day });
+    }
+    let unlock = unlock_time(year, day);
+    if now < unlock {
+        return Err(CalendarError::Locked {
+            year,
+            day,
+            unlock,
+            remaining: format_duration(unlock - now),
+        });
+    }
+    Ok(())
+}
+
+/// Formats a duration as e.g. `2d 3h 04m 05s`, leaving out leading zero
+/// units.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.num_seconds().max(0);
+    let (days, hours, mins, secs) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+    if days > 0 {
+        format!("{}d {}h {:02}m {:02}s", days, hours, mins, secs)
+    } else if hours > 0 {
+        format!("{}h {:02}m {:02}s", hours, mins, secs)
+    } else if mins > 0 {
+        format!("{}m {:02}s", mins, secs)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CalendarError {
+    #[error("{year} day {day} unlocks at {unlock}, in {remaining}")]
+    Locked {
+        year: i32,
+        day: i32,
+        unlock: DateTime<Utc>,
+        remaining: String,
+    },
+    #[error("there is no puzzle for {year} day {day}")]
+    NoSuchDay { year: i32, day: i32 },
+    #[error("no puzzle of {0} has unlocked yet")]
+    NothingUnlocked(i32),
+    #[error("invalid day {0}, expected a number or `today`")]
+    InvalidDay(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(month: u32, day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2023, month, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn default_years() {
+        assert_eq!(default_year(utc(12, 1, 5)), 2023);
+        assert_eq!(default_year(utc(12, 1, 4)), 2022);
+        assert_eq!(default_year(utc(7, 1, 0)), 2022);
+    }
+
+    #[test]
+    fn upcoming_years() {
+        assert_eq!(upcoming_year(utc(12, 1, 4)), 2023);
+        assert_eq!(upcoming_year(utc(11, 30, 12)), 2023);
+        assert_eq!(upcoming_year(utc(12, 26, 0)), 2023);
+        assert_eq!(
+            upcoming_year(Utc.with_ymd_and_hms(2024, 1, 1, 4, 0, 0).unwrap()),
+            2023
+        );
+    }
+
+    #[test]
+    fn latest_days() {
+        assert_eq!(latest_day(2023, utc(12, 3, 4)).unwrap(), 2);
+        assert_eq!(latest_day(2023, utc(12, 3, 5)).unwrap(), 3);
+        assert!(latest_day(2023, utc(11, 30, 12)).is_err());
+        assert_eq!(latest_day(2022, utc(1, 1, 0)).unwrap(), 25);
+        assert_eq!(parse_day(2023, "today", utc(12, 3, 5)).unwrap(), 3);
+        assert_eq!(parse_day(2023, "7", utc(12, 3, 5)).unwrap(), 7);
+        assert!(parse_day(2023, "tomorrow", utc(12, 3, 5)).is_err());
+        assert!(parse_day(2023, "0", utc(12, 3, 5)).is_err());
+        assert!(parse_day(2023, "40", utc(12, 3, 5)).is_err());
+        assert!(parse_day(2025, "13", utc(12, 3, 5)).is_err());
+    }
+
+    #[test]
+    fn unlocking() {
+        assert!(check_unlocked(2023, 3, utc(12, 3, 5)).is_ok());
+        assert!(check_unlocked(2023, 26, utc(12, 31, 0)).is_err());
+        assert_eq!(
+            check_unlocked(2023, 3, utc(12, 2, 3))
+                .unwrap_err()
+                .to_string(),
+            "2023 day 3 unlocks at 2023-12-03 05:00:00 UTC, in 1d 2h 00m 00s"
+        );
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(format_duration(Duration::seconds(5)), "5s");
+        assert_eq!(format_duration(Duration::seconds(65)), "1m 05s");
+        assert_eq!(format_duration(Duration::seconds(3605)), "1h 00m 05s");
+        assert_eq!(format_duration(Duration::seconds(-3)), "0s");
+    }
+}
@@ -1,11 +1,37 @@
+use crate::account::Account;
+use crate::cache::{Cache, CacheError};
+use crate::calendar;
+use crate::config::Config;
+use crate::http::{HttpError, Origin, Response, Transport, DEFAULT_BASE_URL};
+use crate::leaderboard::Leaderboard;
+use crate::puzzle::Puzzle;
+use crate::throttle::{self, SystemClock, Throttle};
+use chrono::Utc;
 use failure::Error;
-use native_tls::TlsConnector;
-use std::collections::HashMap;
+use lazy_static::lazy_static;
+use regex::Regex;
 use std::env::{self, VarError};
-use std::io::{BufRead, BufReader, Read, Write};
-use std::net::TcpStream;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, BufReader, Write};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
 pub const AOC_SESSION_ENV: &str = "AOC_SESSION";
+pub const AOC_BASE_URL_ENV: &str = "AOC_BASE_URL";
+
+/// The site asks not to poll private leaderboards more than once every 15
+/// minutes.
+const LEADERBOARD_MAX_AGE: Duration = Duration::from_secs(15 * 60);
+
+const MAX_REDIRECTS: usize = 5;
+
+/// Minimum time between the start of consecutive requests.
+const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+const MAX_RETRIES: u32 = 3;
+
+/// Longer `Retry-After` delays are not waited out.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
 
 pub fn get_session_token() -> Result<String, VarError> {
     env::var(AOC_SESSION_ENV)
@@ -13,122 +39,1045 @@ pub fn get_session_token() -> Result<String, VarError> {
 
 #[derive(Clone, Debug)]
 pub struct Client {
-    session: String,
+    session: Option<String>,
+    offline: bool,
+    cache: Option<Cache>,
+    origin: Origin,
+    transport: Arc<dyn Transport>,
+    throttle: Arc<Throttle>,
 }
 
 impl Client {
+    #[cfg(test)]
     pub fn new<S: Into<String>>(session: S) -> Client {
+        let origin = DEFAULT_BASE_URL.parse::<Origin>().expect("valid default");
         Client {
-            session: session.into(),
+            session: Some(session.into()),
+            offline: false,
+            cache: None,
+            transport: origin.transport(),
+            origin,
+            throttle: Arc::new(Throttle::new(Arc::new(SystemClock), MIN_INTERVAL)),
         }
     }
 
-    pub fn from_env() -> Result<Client, Error> {
-        let session = get_session_token()?;
-        Ok(Client::new(session))
+    /// Uses the session and base URL from `$AOC_SESSION` and `$AOC_BASE_URL`
+    /// or the config file. Without a session the client still serves cached
+    /// data, and only fails once it needs the network.
+    pub fn from_config(config: &Config) -> Result<Client, Error> {
+        let base_url = env::var(AOC_BASE_URL_ENV)
+            .ok()
+            .filter(|url| !url.is_empty())
+            .or_else(|| config.base_url.clone())
+            .unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+        let origin = base_url.parse::<Origin>()?;
+        Ok(Client {
+            session: get_session_token()
+                .ok()
+                .filter(|session| !session.is_empty())
+                .or_else(|| config.session.clone()),
+            offline: false,
+            cache: None,
+            transport: origin.transport(),
+            origin,
+            throttle: Arc::new(Throttle::new(Arc::new(SystemClock), MIN_INTERVAL)),
+        })
+    }
+
+    /// Creates a client that never touches the network and only serves
+    /// inputs that are already in the cache.
+    pub fn offline(cache: Cache) -> Client {
+        let origin = DEFAULT_BASE_URL.parse::<Origin>().expect("valid default");
+        Client {
+            session: None,
+            offline: true,
+            cache: Some(cache),
+            transport: origin.transport(),
+            origin,
+            throttle: Arc::new(Throttle::new(Arc::new(SystemClock), MIN_INTERVAL)),
+        }
+    }
+
+    pub fn with_cache(mut self, cache: Cache) -> Client {
+        self.cache = Some(cache);
+        self
     }
 
     pub fn get_input(&self, year: i32, day: i32) -> Result<String, Error> {
-        let connector = TlsConnector::new()?;
-        let stream = TcpStream::connect("adventofcode.com:443")?;
-        let mut stream = connector.connect("adventofcode.com", stream)?;
+        if let Some(cache) = &self.cache {
+            if let Some(input) = cache.read_input(year, day)? {
+                return Ok(input);
+            }
+        }
 
-        let nl = "\r\n";
-        write!(stream, "GET /{}/day/{}/input HTTP/1.1{}", year, day, nl)?;
-        write!(stream, "Host: adventofcode.com{}", nl)?;
-        write!(stream, "Accept: text/plain{}", nl)?;
-        write!(stream, "Connection: close{}", nl)?;
-        write!(stream, "Cookie: session={}{}", self.session, nl)?;
-        write!(stream, "User-Agent: aoc-rs/0.1.0{}", nl)?;
-        write!(stream, "{}{}", nl, nl)?;
+        if self.offline {
+            let path = self
+                .cache
+                .as_ref()
+                .map(|cache| cache.input_path(year, day).display().to_string())
+                .unwrap_or_default();
+            return Err(CacheError::MissingInput { year, day, path }.into());
+        }
 
-        let mut reader = BufReader::new(stream);
-        let mut buf = String::new();
+        calendar::check_unlocked(year, day, Utc::now())?;
+        let input = self.request("GET", &format!("/{}/day/{}/input", year, day), None)?;
+        if let Some(cache) = &self.cache {
+            cache.write_input(year, day, &input)?;
+        }
 
-        macro_rules! read_line {
-            () => {{
-                buf.clear();
-                let bytes = reader.read_line(&mut buf)?;
-                if bytes == 0 {
-                    return Err(HttpError::UnexpectedEndOfStream.into());
+        Ok(input)
+    }
+
+    /// Gets the puzzle description page. Cached pages are refetched while
+    /// they only contain part 1, unless the client is offline.
+    pub fn get_puzzle(&self, year: i32, day: i32) -> Result<Puzzle, Error> {
+        let cached = match &self.cache {
+            Some(cache) => cache.read_puzzle(year, day)?,
+            None => None,
+        };
+        match &cached {
+            Some(html) => {
+                let puzzle = Puzzle::new(html.as_str());
+                if puzzle.articles().len() >= 2 || self.offline {
+                    return Ok(puzzle);
                 }
-                buf.trim()
-            }};
+            }
+            None if self.offline => {
+                let path = self
+                    .cache
+                    .as_ref()
+                    .map(|cache| cache.puzzle_path(year, day).display().to_string())
+                    .unwrap_or_default();
+                return Err(CacheError::MissingPuzzle { year, day, path }.into());
+            }
+            None => {}
         }
 
-        let status: i32 = {
-            let line = read_line!();
-            let code = match line.split(' ').nth(1) {
-                Some(s) => s,
-                None => return Err(HttpError::InvalidStatusLine.into()),
-            };
-            code.parse()?
+        calendar::check_unlocked(year, day, Utc::now())?;
+        let since = match (&cached, &self.cache) {
+            (Some(_), Some(cache)) => cache.puzzle_modified(year, day)?,
+            _ => None,
         };
-
-        if status != 200 {
-            return Err(HttpError::StatusCode(status).into());
+        let path = format!("/{}/day/{}", year, day);
+        let html = match self.fetch("GET", &path, None, since)? {
+            Some(html) => html,
+            None => cached.expect("only cached pages are requested conditionally"),
+        };
+        if let Some(cache) = &self.cache {
+            cache.write_puzzle(year, day, &html)?;
         }
 
-        let headers = {
-            let mut set = HashMap::<String, String>::new();
-            loop {
-                let line = read_line!();
-                if line.is_empty() {
-                    break; // end of headers
-                }
+        Ok(Puzzle::new(html))
+    }
 
-                let mut pair = line.splitn(2, ':');
-                let (key, value) = match (pair.next(), pair.next()) {
-                    (Some(k), Some(v)) => (k, v),
-                    _ => return Err(HttpError::InvalidHeader.into()),
-                };
+    /// Gets a private leaderboard, reusing a cached copy while it is fresh or
+    /// when offline.
+    pub fn get_leaderboard(&self, year: i32, id: u64) -> Result<Leaderboard, Error> {
+        let cached = match &self.cache {
+            Some(cache) => cache.read_leaderboard(year, id)?,
+            None => None,
+        };
+        match &cached {
+            Some((json, modified)) if self.offline || self.age(*modified) < LEADERBOARD_MAX_AGE => {
+                return Leaderboard::parse(json)
+            }
+            None if self.offline => {
+                let path = self
+                    .cache
+                    .as_ref()
+                    .map(|cache| cache.leaderboard_path(year, id).display().to_string())
+                    .unwrap_or_default();
+                return Err(CacheError::MissingLeaderboard { year, id, path }.into());
+            }
+            _ => {}
+        }
 
-                set.insert(key.trim().to_owned(), value.trim().to_owned());
+        let path = format!("/{}/leaderboard/private/view/{}.json", year, id);
+        let since = cached.as_ref().map(|(_, modified)| *modified);
+        let json = match self.fetch("GET", &path, None, since)? {
+            Some(json) => json,
+            None => {
+                cached
+                    .expect("only cached leaderboards are requested conditionally")
+                    .0
             }
-            set
         };
+        let leaderboard = Leaderboard::parse(&json)?;
+        if let Some(cache) = &self.cache {
+            cache.write_leaderboard(year, id, &json)?;
+        }
 
-        let content_length: usize = {
-            headers
-                .get("Content-Length")
-                .map(|s| &**s)
-                .unwrap_or("0")
-                .parse()?
-        };
+        Ok(leaderboard)
+    }
 
-        let input = {
-            let mut input_buf = vec![0u8; content_length];
-            reader.read_exact(&mut input_buf)?;
-            String::from_utf8(input_buf)?
-        };
+    /// Checks that the session is logged in, returning whose account it is
+    /// along with their stars.
+    pub fn validate_session(&self) -> Result<Account, Error> {
+        let year = calendar::default_year(Utc::now());
+        let html = self.request("GET", &format!("/{}/events", year), None)?;
+        Ok(Account::parse(&html).ok_or(ClientError::InvalidSession)?)
+    }
 
-        Ok(input)
+    /// Whether an input can be served without fetching it.
+    pub fn has_input(&self, year: i32, day: i32) -> bool {
+        self.cache
+            .as_ref()
+            .is_some_and(|cache| cache.input_path(year, day).exists())
+    }
+
+    /// Submits an answer for one part of a puzzle.
+    pub fn submit_answer(
+        &self,
+        year: i32,
+        day: i32,
+        part: u8,
+        answer: &str,
+    ) -> Result<Submission, Error> {
+        let body = format!("level={}&answer={}", part, urlencode(answer));
+        let html = self.request(
+            "POST",
+            &format!("/{}/day/{}/answer", year, day),
+            Some(("application/x-www-form-urlencoded", &body)),
+        )?;
+        parse_submission(&html)
+    }
+
+    /// Sends requests to another site, such as a local mock server, with
+    /// the default transport for its scheme.
+    #[cfg(test)]
+    pub fn with_base_url(mut self, base_url: &str) -> Result<Client, Error> {
+        self.origin = base_url.parse()?;
+        self.transport = self.origin.transport();
+        Ok(self)
+    }
+
+    #[cfg(test)]
+    pub fn with_transport(mut self, transport: Arc<dyn Transport>) -> Client {
+        self.transport = transport;
+        self
+    }
+
+    #[cfg(test)]
+    pub fn with_clock(mut self, clock: Arc<dyn throttle::Clock>) -> Client {
+        self.throttle = Arc::new(Throttle::new(clock, MIN_INTERVAL));
+        self
+    }
+
+    fn age(&self, time: SystemTime) -> Duration {
+        let now = self.throttle.clock().now();
+        now.duration_since(time).unwrap_or_default()
+    }
+
+    fn request(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<(&str, &str)>,
+    ) -> Result<String, Error> {
+        match self.fetch(method, path, body, None)? {
+            Some(text) => Ok(text),
+            None => Err(HttpError::StatusCode(304).into()),
+        }
+    }
+
+    /// Sends a request, following redirects, and returns the body of the
+    /// final response. With `since`, the request is conditional and returns
+    /// `None` if the resource has not been modified since.
+    fn fetch(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<(&str, &str)>,
+        since: Option<SystemTime>,
+    ) -> Result<Option<String>, Error> {
+        if self.offline {
+            return Err(ClientError::Offline.into());
+        }
+        let session = self.session.as_ref().ok_or_else(|| {
+            let config = Config::path().map(|path| path.display().to_string());
+            ClientError::NoSession(config.unwrap_or_else(|| "the config file".to_string()))
+        })?;
+        let (mut method, mut path, mut body) = (method, path.to_owned(), body);
+        for _ in 0..=MAX_REDIRECTS {
+            let response = self
+                .send_with_retries(session, method, &path, body, since)
+                .map_err(|err| self.network_error(err))?;
+            if response.status == 304 && since.is_some() {
+                return Ok(None);
+            }
+            if !response.is_redirect() {
+                if response.status != 200 {
+                    return Err(status_error(&path, response));
+                }
+                return response.text().map(Some);
+            }
+
+            let location = response
+                .header("Location")
+                .ok_or(HttpError::InvalidHeader)?;
+            path = redirect_path(&self.origin, location)?;
+            if response.status == 303 || (method == "POST" && response.status <= 302) {
+                method = "GET";
+                body = None;
+            }
+        }
+        Err(HttpError::TooManyRedirects.into())
+    }
+
+    /// Tells connection failures apart from other errors.
+    fn network_error(&self, err: Error) -> Error {
+        let host = self.origin.authority();
+        match err.downcast::<io::Error>() {
+            Ok(err) => ClientError::Unreachable(host, err).into(),
+            Err(err) => match err.downcast::<native_tls::Error>() {
+                Ok(err) => ClientError::Tls(host, err).into(),
+                Err(err) => err,
+            },
+        }
+    }
+
+    /// Sends a throttled request, retrying GET requests that failed to
+    /// connect or were turned away with a transient status. Submissions are
+    /// never retried, since the first attempt may have been counted.
+    fn send_with_retries(
+        &self,
+        session: &str,
+        method: &str,
+        path: &str,
+        body: Option<(&str, &str)>,
+        since: Option<SystemTime>,
+    ) -> Result<Response, Error> {
+        let mut attempt = 0;
+        loop {
+            self.throttle.wait();
+            let result = self.send(session, method, path, body, since);
+            let delay = match &result {
+                _ if method != "GET" || attempt == MAX_RETRIES => return result,
+                Ok(response) if matches!(response.status, 429 | 500 | 502 | 503 | 504) => {
+                    match response.header("Retry-After") {
+                        Some(value) => {
+                            let now = self.throttle.clock().now();
+                            match throttle::retry_after(value, now) {
+                                Some(delay) if delay <= MAX_RETRY_DELAY => delay,
+                                Some(_) => return result,
+                                None => throttle::backoff(attempt),
+                            }
+                        }
+                        None => throttle::backoff(attempt),
+                    }
+                }
+                Err(err) if is_transient(err) => throttle::backoff(attempt),
+                _ => return result,
+            };
+            self.throttle.clock().sleep(delay);
+            attempt += 1;
+        }
+    }
+
+    fn send(
+        &self,
+        session: &str,
+        method: &str,
+        path: &str,
+        body: Option<(&str, &str)>,
+        since: Option<SystemTime>,
+    ) -> Result<Response, Error> {
+        let mut stream = self
+            .transport
+            .connect(&self.origin.host, self.origin.port)?;
+
+        let nl = "\r\n";
+        write!(stream, "{} {} HTTP/1.1{}", method, path, nl)?;
+        write!(stream, "Host: {}{}", self.origin.authority(), nl)?;
+        write!(stream, "Accept: text/plain{}", nl)?;
+        write!(stream, "Connection: close{}", nl)?;
+        write!(stream, "Cookie: session={}{}", session, nl)?;
+        write!(stream, "User-Agent: aoc-rs/0.1.0{}", nl)?;
+        if let Some(since) = since {
+            write!(
+                stream,
+                "If-Modified-Since: {}{}",
+                throttle::http_date(since),
+                nl
+            )?;
+        }
+        if let Some((content_type, body)) = body {
+            write!(stream, "Content-Type: {}{}", content_type, nl)?;
+            write!(stream, "Content-Length: {}{}", body.len(), nl)?;
+            write!(stream, "{}{}", nl, body)?;
+        } else {
+            write!(stream, "{}", nl)?;
+        }
+        stream.flush()?;
+
+        Response::read(BufReader::new(stream), method)
+    }
+}
+
+/// Maps the responses the site gives for a bad session or a puzzle that is
+/// locked or missing.
+fn status_error(path: &str, response: Response) -> Error {
+    let status = response.status;
+    let body = response.text().unwrap_or_default();
+    match status {
+        400 | 401 | 403 => ClientError::InvalidSession.into(),
+        404 if body.contains("before it unlocks") => {
+            ClientError::NotUnlocked(path.to_owned()).into()
+        }
+        404 => ClientError::NotFound(path.to_owned()).into(),
+        _ => HttpError::StatusCode(i32::from(status)).into(),
+    }
+}
+
+/// Connection failures and dropped connections are worth retrying.
+fn is_transient(err: &Error) -> bool {
+    err.downcast_ref::<io::Error>().is_some()
+        || matches!(
+            err.downcast_ref::<HttpError>(),
+            Some(HttpError::UnexpectedEndOfStream)
+        )
+}
+
+/// Resolves a redirect location to a path on the same site, refusing to send
+/// the session cookie anywhere else.
+fn redirect_path(origin: &Origin, location: &str) -> Result<String, HttpError> {
+    let path = location
+        .strip_prefix(&origin.to_string())
+        .unwrap_or(location);
+    if path.is_empty() {
+        Ok("/".to_string())
+    } else if path.starts_with('/') && !path.starts_with("//") {
+        Ok(path.to_string())
+    } else {
+        Err(HttpError::ForeignRedirect(location.to_string()))
+    }
+}
+
+fn urlencode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// The outcome of submitting an answer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Submission {
+    Correct,
+    TooHigh,
+    TooLow,
+    Wrong,
+    RateLimited(Duration),
+    AlreadySolved,
+}
+
+impl Display for Submission {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Submission::Correct => write!(f, "correct"),
+            Submission::TooHigh => write!(f, "wrong (too high)"),
+            Submission::TooLow => write!(f, "wrong (too low)"),
+            Submission::Wrong => write!(f, "wrong"),
+            Submission::RateLimited(wait) => {
+                write!(f, "rate limited, wait {}s", wait.as_secs())
+            }
+            Submission::AlreadySolved => write!(f, "already solved"),
+        }
     }
 }
 
+lazy_static! {
+    static ref WAIT_RE: Regex = Regex::new(r"You have (?:(\d+)m )?(\d+)s left to wait").unwrap();
+}
+
+fn parse_submission(html: &str) -> Result<Submission, Error> {
+    let submission = if html.contains("That's the right answer") {
+        Submission::Correct
+    } else if html.contains("your answer is too high") {
+        Submission::TooHigh
+    } else if html.contains("your answer is too low") {
+        Submission::TooLow
+    } else if html.contains("That's not the right answer") {
+        Submission::Wrong
+    } else if html.contains("You gave an answer too recently") {
+        let wait = WAIT_RE
+            .captures(html)
+            .map(|caps| {
+                let minutes = caps.get(1).map_or(0, |m| m.as_str().parse().unwrap());
+                let seconds: u64 = caps[2].parse().unwrap();
+                Duration::from_secs(minutes * 60 + seconds)
+            })
+            .unwrap_or_default();
+        Submission::RateLimited(wait)
+    } else if html.contains("You don't seem to be solving the right level") {
+        Submission::AlreadySolved
+    } else {
+        return Err(ClientError::UnrecognizedSubmission.into());
+    };
+    Ok(submission)
+}
+
 #[derive(Debug, thiserror::Error)]
-pub enum HttpError {
-    #[error("unexpected end of stream")]
-    UnexpectedEndOfStream,
-    #[error("invalid HTTP status line format")]
-    InvalidStatusLine,
-    #[error("invalid HTTP header format")]
-    InvalidHeader,
-    #[error("HTTP error code {0}")]
-    StatusCode(i32),
+pub enum ClientError {
+    #[error("cannot access adventofcode.com in offline mode")]
+    Offline,
+    #[error("unrecognized answer submission response")]
+    UnrecognizedSubmission,
+    #[error("no adventofcode.com session configured, set $AOC_SESSION or `session` in {0}, or use --offline")]
+    NoSession(String),
+    #[error("the adventofcode.com session was rejected, it may have expired; log in again and update $AOC_SESSION or `session` in the config file")]
+    InvalidSession,
+    #[error("{0} has not unlocked yet, use --wait to wait for it")]
+    NotUnlocked(String),
+    #[error("{0} does not exist, check that the event and day are right")]
+    NotFound(String),
+    #[error("could not reach {0}, check the network connection: {1}")]
+    Unreachable(String, io::Error),
+    #[error("secure connection to {0} failed: {1}")]
+    Tls(String, native_tls::Error),
 }
 
 #[cfg(test)]
-mod tests {
-    use super::Client;
+pub mod tests {
+    use super::*;
+    use crate::cache::tests::temp_cache;
+    use crate::http::Stream;
+    use crate::throttle::tests::FakeClock;
+    use std::collections::VecDeque;
+    use std::io::{BufRead, Cursor, Read};
+    use std::net::TcpListener;
+    use std::sync::Mutex;
+    use std::thread::{self, JoinHandle};
+
+    /// Serves a single canned `response` on a local port, returning the
+    /// server's base URL and a handle resolving to the raw request received.
+    pub fn serve(response: &str) -> (String, JoinHandle<String>) {
+        let (url, handle) = serve_all(&[response]);
+        (url, thread::spawn(move || handle.join().unwrap().remove(0)))
+    }
+
+    /// Serves canned `responses` to consecutive connections, resolving to
+    /// the raw requests received.
+    pub fn serve_all(responses: &[&str]) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let responses = responses.iter().map(|&r| r.to_owned()).collect::<Vec<_>>();
+        let handle = thread::spawn(move || {
+            responses
+                .into_iter()
+                .map(|response| {
+                    let (stream, _) = listener.accept().unwrap();
+                    let mut reader = BufReader::new(stream);
+                    let mut request = String::new();
+                    let mut content_length = 0;
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        if let Some(length) = line.strip_prefix("Content-Length:") {
+                            content_length = length.trim().parse().unwrap();
+                        }
+                        request.push_str(&line);
+                        if line == "\r\n" {
+                            break;
+                        }
+                    }
+                    let mut body = vec![0; content_length];
+                    reader.read_exact(&mut body).unwrap();
+                    request.push_str(&String::from_utf8(body).unwrap());
+
+                    reader.get_mut().write_all(response.as_bytes()).unwrap();
+                    request
+                })
+                .collect()
+        });
+        (url, handle)
+    }
+
+    /// A transport answering each connection with the next canned response,
+    /// without touching the network.
+    #[derive(Debug, Default)]
+    pub struct Canned {
+        responses: Mutex<VecDeque<String>>,
+        requests: Arc<Mutex<Vec<Vec<u8>>>>,
+    }
+
+    impl Canned {
+        pub fn new(responses: &[&str]) -> Arc<Canned> {
+            Arc::new(Canned {
+                responses: Mutex::new(responses.iter().map(|&r| r.to_owned()).collect()),
+                requests: Arc::default(),
+            })
+        }
+
+        /// The raw requests received so far.
+        pub fn requests(&self) -> Vec<String> {
+            let requests = self.requests.lock().unwrap();
+            requests
+                .iter()
+                .map(|request| String::from_utf8_lossy(request).into_owned())
+                .collect()
+        }
+    }
+
+    impl Transport for Canned {
+        fn connect(&self, _host: &str, _port: u16) -> Result<Box<dyn Stream>, Error> {
+            let response = self.responses.lock().unwrap().pop_front();
+            let response = response.ok_or_else(|| {
+                io::Error::new(io::ErrorKind::ConnectionRefused, "no canned response left")
+            })?;
+            let mut requests = self.requests.lock().unwrap();
+            requests.push(Vec::new());
+            Ok(Box::new(Exchange {
+                response: Cursor::new(response.into_bytes()),
+                requests: self.requests.clone(),
+                index: requests.len() - 1,
+            }))
+        }
+    }
+
+    struct Exchange {
+        response: Cursor<Vec<u8>>,
+        requests: Arc<Mutex<Vec<Vec<u8>>>>,
+        index: usize,
+    }
+
+    impl Read for Exchange {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.response.read(buf)
+        }
+    }
+
+    impl Write for Exchange {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.requests.lock().unwrap()[self.index].extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    pub fn html_response(body: &str) -> String {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+    }
 
     #[test]
     fn test_client() {
-        let client = match Client::from_env() {
-            Ok(client) => client,
-            Err(_) => return, // skip test for non-configured systems
-        };
+        let client = Client::from_config(&Config::from_env().unwrap()).unwrap();
+        if client.session.is_none() {
+            return; // skip test for non-configured systems
+        }
         let input = client.get_input(2018, 1).unwrap();
         assert!(!input.is_empty());
     }
+
+    #[test]
+    fn offline_uses_cache() {
+        let cache = temp_cache();
+        cache.write_input(2023, 1, "cached input").unwrap();
+
+        let client = Client::offline(cache);
+        assert_eq!(client.get_input(2023, 1).unwrap(), "cached input");
+    }
+
+    #[test]
+    fn offline_missing_input() {
+        let client = Client::offline(temp_cache());
+        let err = client.get_input(2023, 2).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<CacheError>(),
+            Some(CacheError::MissingInput {
+                year: 2023,
+                day: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn submit_answer() {
+        let (url, server) = serve(&html_response(
+            "<article><p>That's the right answer!  You are one gold star closer.</p></article>",
+        ));
+        let client = Client::new("secret").with_base_url(&url).unwrap();
+
+        let submission = client.submit_answer(2023, 5, 2, "42 7").unwrap();
+        assert_eq!(submission, Submission::Correct);
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /2023/day/5/answer HTTP/1.1\r\n"));
+        assert!(request.contains("Cookie: session=secret\r\n"));
+        assert!(request.ends_with("\r\n\r\nlevel=2&answer=42%207"));
+    }
+
+    #[test]
+    fn submission_responses() {
+        for (html, expected) in &[
+            (
+                "That's not the right answer; your answer is too high.",
+                Submission::TooHigh,
+            ),
+            (
+                "That's not the right answer; your answer is too low.",
+                Submission::TooLow,
+            ),
+            (
+                "That's not the right answer.  If you're stuck, make sure...",
+                Submission::Wrong,
+            ),
+            (
+                "You gave an answer too recently; you have to wait after submitting an \
+                 answer before trying again.  You have 1m 5s left to wait.",
+                Submission::RateLimited(Duration::from_secs(65)),
+            ),
+            (
+                "You gave an answer too recently.  You have 30s left to wait.",
+                Submission::RateLimited(Duration::from_secs(30)),
+            ),
+            (
+                "You don't seem to be solving the right level.  Did you already complete it?",
+                Submission::AlreadySolved,
+            ),
+        ] {
+            assert_eq!(parse_submission(html).unwrap(), *expected);
+        }
+        assert!(parse_submission("<html></html>").is_err());
+    }
+
+    #[test]
+    fn missing_session() {
+        let cache = temp_cache();
+        cache.write_input(2023, 1, "cached input").unwrap();
+        let client = Client::from_config(&Config::default())
+            .unwrap()
+            .with_cache(cache);
+        if client.session.is_some() {
+            return; // $AOC_SESSION is set
+        }
+
+        assert_eq!(client.get_input(2023, 1).unwrap(), "cached input");
+        let err = client.get_input(2023, 2).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ClientError>(),
+            Some(ClientError::NoSession(_))
+        ));
+        assert!(err
+            .to_string()
+            .contains("set $AOC_SESSION or `session` in "));
+    }
+
+    #[test]
+    fn offline_submit() {
+        let client = Client::offline(temp_cache());
+        let err = client.submit_answer(2023, 1, 1, "1").unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ClientError>(),
+            Some(ClientError::Offline)
+        ));
+    }
+
+    #[test]
+    fn get_puzzle() {
+        let (url, server) = serve(&html_response(crate::puzzle::tests::PAGE));
+        let cache = temp_cache();
+        let client = Client::new("secret")
+            .with_cache(cache.clone())
+            .with_base_url(&url)
+            .unwrap();
+
+        let puzzle = client.get_puzzle(2023, 1).unwrap();
+        assert_eq!(puzzle.articles().len(), 2);
+        assert!(server
+            .join()
+            .unwrap()
+            .starts_with("GET /2023/day/1 HTTP/1.1\r\n"));
+
+        // Served from the cache now that both parts are present.
+        let puzzle = Client::offline(cache).get_puzzle(2023, 1).unwrap();
+        assert_eq!(puzzle.articles().len(), 2);
+    }
+
+    #[test]
+    fn get_leaderboard() {
+        let json = crate::leaderboard::tests::LEADERBOARD;
+        let (url, server) = serve(&format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            json.len(),
+            json
+        ));
+        let cache = temp_cache();
+        let client = Client::new("secret")
+            .with_cache(cache.clone())
+            .with_base_url(&url)
+            .unwrap();
+
+        let leaderboard = client.get_leaderboard(2023, 1001).unwrap();
+        assert_eq!(leaderboard.members.len(), 4);
+        assert!(server
+            .join()
+            .unwrap()
+            .starts_with("GET /2023/leaderboard/private/view/1001.json HTTP/1.1\r\n"));
+
+        // A fresh copy is served from the cache without another request.
+        let leaderboard = client.get_leaderboard(2023, 1001).unwrap();
+        assert_eq!(leaderboard.year(), 2023);
+        assert!(Client::offline(cache).get_leaderboard(2023, 1002).is_err());
+    }
+
+    #[test]
+    fn follow_redirects() {
+        let canned = Canned::new(&[
+            "HTTP/1.1 303 See Other\r\nlocation: https://adventofcode.com/2023/day/1\r\n\r\n",
+            "HTTP/1.1 200 OK\r\ntransfer-encoding: chunked\r\n\r\n5\r\n1 2 3\r\n0\r\n\r\n",
+        ]);
+        let client = Client::new("secret")
+            .with_transport(canned.clone())
+            .with_clock(FakeClock::new());
+        assert_eq!(
+            client
+                .request("POST", "/2023/day/1/answer", Some(("text/plain", "x")))
+                .unwrap(),
+            "1 2 3"
+        );
+        let requests = canned.requests();
+        assert!(requests[0].starts_with("POST /2023/day/1/answer HTTP/1.1\r\n"));
+        assert!(requests[1].starts_with("GET /2023/day/1 HTTP/1.1\r\n"));
+        assert!(!requests[1].contains("Content-Length"));
+
+        let canned = Canned::new(&["HTTP/1.1 302 Found\r\nLocation: https://example.com/\r\n\r\n"]);
+        let client = Client::new("secret").with_transport(canned);
+        assert_eq!(
+            client
+                .request("GET", "/2023/day/1", None)
+                .unwrap_err()
+                .to_string(),
+            "refusing to follow redirect to https://example.com/"
+        );
+
+        let loops = vec![
+            "HTTP/1.1 302 Found\r\nLocation: /\r\nContent-Length: 0\r\n\r\n";
+            MAX_REDIRECTS + 1
+        ];
+        let client = Client::new("secret")
+            .with_transport(Canned::new(&loops))
+            .with_clock(FakeClock::new());
+        assert_eq!(
+            client.request("GET", "/", None).unwrap_err().to_string(),
+            "too many redirects"
+        );
+    }
+
+    fn text_response(status: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        )
+    }
+
+    #[test]
+    fn input_responses() {
+        let ok = text_response("200 OK", "1 2 3\n");
+        let canned = Canned::new(&[&ok]);
+        let cache = temp_cache();
+        let client = Client::new("secret")
+            .with_cache(cache.clone())
+            .with_transport(canned.clone());
+        assert_eq!(client.get_input(2015, 1).unwrap(), "1 2 3\n");
+        assert_eq!(
+            cache.read_input(2015, 1).unwrap().as_deref(),
+            Some("1 2 3\n")
+        );
+        let request = &canned.requests()[0];
+        assert!(request.starts_with("GET /2015/day/1/input HTTP/1.1\r\n"));
+        assert!(request.contains("Host: adventofcode.com\r\n"));
+        assert!(request.contains("Cookie: session=secret\r\n"));
+
+        let not_unlocked = text_response(
+            "404 Not Found",
+            "Please don't repeatedly request this endpoint before it unlocks!",
+        );
+        let bad_session = text_response(
+            "400 Bad Request",
+            "Puzzle inputs differ by user.  Please log in to get your puzzle input.",
+        );
+        let not_found = text_response("404 Not Found", "404 Not Found");
+        let server_error = text_response("500 Internal Server Error", "");
+        let cases = [
+            (not_unlocked, "/2015/day/2/input has not unlocked yet, use --wait to wait for it"),
+            (bad_session, "the adventofcode.com session was rejected, it may have expired; log in again and update $AOC_SESSION or `session` in the config file"),
+            (not_found, "/2015/day/2/input does not exist, check that the event and day are right"),
+            (server_error, "HTTP error code 500"),
+        ];
+        for (response, message) in cases {
+            // Server errors are retried before giving up.
+            let responses = vec![response.as_str(); MAX_RETRIES as usize + 1];
+            let cache = temp_cache();
+            let client = Client::new("secret")
+                .with_cache(cache.clone())
+                .with_transport(Canned::new(&responses))
+                .with_clock(FakeClock::new());
+            assert_eq!(client.get_input(2015, 2).unwrap_err().to_string(), message);
+            assert_eq!(cache.read_input(2015, 2).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn network_errors() {
+        let client = Client::new("secret")
+            .with_transport(Canned::new(&[]))
+            .with_clock(FakeClock::new());
+        let err = client.get_input(2015, 1).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ClientError>(),
+            Some(ClientError::Unreachable(host, _)) if host == "adventofcode.com"
+        ));
+    }
+
+    #[test]
+    fn base_url() {
+        let (url, server) = serve(&text_response("200 OK", "4 5 6"));
+        let client = Client::new("secret").with_base_url(&url).unwrap();
+        assert_eq!(client.get_input(2015, 3).unwrap(), "4 5 6");
+        let authority = url.trim_start_matches("http://");
+        assert!(server
+            .join()
+            .unwrap()
+            .contains(&format!("Host: {}\r\n", authority)));
+
+        assert!(Client::new("secret")
+            .with_base_url("adventofcode.com")
+            .is_err());
+    }
+
+    #[test]
+    fn throttle_requests() {
+        let ok = text_response("200 OK", "1");
+        let clock = FakeClock::new();
+        let client = Client::new("secret")
+            .with_transport(Canned::new(&[&ok, &ok]))
+            .with_clock(clock.clone());
+        client.get_input(2015, 1).unwrap();
+        client.clone().get_input(2015, 2).unwrap();
+        assert_eq!(clock.sleeps(), vec![MIN_INTERVAL]);
+    }
+
+    #[test]
+    fn retry_transient_failures() {
+        let unavailable = text_response("503 Service Unavailable", "");
+        let too_many =
+            "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 7\r\nContent-Length: 0\r\n\r\n";
+        let ok = text_response("200 OK", "1 2 3");
+        let clock = FakeClock::new();
+        let client = Client::new("secret")
+            .with_transport(Canned::new(&[&unavailable, too_many, &ok]))
+            .with_clock(clock.clone());
+        assert_eq!(client.get_input(2015, 1).unwrap(), "1 2 3");
+        assert_eq!(
+            clock.sleeps(),
+            vec![throttle::backoff(0), Duration::from_secs(7)]
+        );
+
+        // Connection failures back off exponentially until giving up.
+        let clock = FakeClock::new();
+        let client = Client::new("secret")
+            .with_transport(Canned::new(&[]))
+            .with_clock(clock.clone());
+        assert!(client.get_input(2015, 1).is_err());
+        let backoffs = (0..MAX_RETRIES).map(throttle::backoff).collect::<Vec<_>>();
+        assert_eq!(clock.sleeps(), backoffs);
+
+        // Too long a wait is not worth it.
+        let later =
+            "HTTP/1.1 503 Service Unavailable\r\nRetry-After: 3600\r\nContent-Length: 0\r\n\r\n";
+        let clock = FakeClock::new();
+        let client = Client::new("secret")
+            .with_transport(Canned::new(&[later]))
+            .with_clock(clock.clone());
+        assert_eq!(
+            client.get_input(2015, 1).unwrap_err().to_string(),
+            "HTTP error code 503"
+        );
+        assert!(clock.sleeps().is_empty());
+    }
+
+    #[test]
+    fn no_submission_retries() {
+        let clock = FakeClock::new();
+        let client = Client::new("secret")
+            .with_transport(Canned::new(&[&text_response("502 Bad Gateway", "")]))
+            .with_clock(clock.clone());
+        assert!(client.submit_answer(2015, 1, 1, "42").is_err());
+        assert!(clock.sleeps().is_empty());
+    }
+
+    #[test]
+    fn conditional_requests() {
+        let cache = temp_cache();
+        let json = crate::leaderboard::tests::LEADERBOARD;
+        cache.write_leaderboard(2023, 1001, json).unwrap();
+        let clock = FakeClock::new();
+        let canned = Canned::new(&["HTTP/1.1 304 Not Modified\r\n\r\n"]);
+        let client = Client::new("secret")
+            .with_cache(cache.clone())
+            .with_transport(canned.clone())
+            .with_clock(clock.clone());
+
+        // Fresh copies are used without asking.
+        client.get_leaderboard(2023, 1001).unwrap();
+        assert!(canned.requests().is_empty());
+
+        clock.advance(LEADERBOARD_MAX_AGE);
+        let leaderboard = client.get_leaderboard(2023, 1001).unwrap();
+        assert_eq!(leaderboard.members.len(), 4);
+        let requests = canned.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].contains("If-Modified-Since: "));
+
+        // Part 1 only pages are checked for part 2.
+        let page = crate::puzzle::tests::PAGE;
+        let part1 = &page[..page.rfind("<article").unwrap()];
+        cache.write_puzzle(2023, 1, part1).unwrap();
+        let canned = Canned::new(&["HTTP/1.1 304 Not Modified\r\n\r\n"]);
+        let client = Client::new("secret")
+            .with_cache(cache)
+            .with_transport(canned.clone());
+        assert_eq!(client.get_puzzle(2023, 1).unwrap().articles().len(), 1);
+        assert!(canned.requests()[0].contains("If-Modified-Since: "));
+
+        // Unconditional requests treat it like any other unexpected status.
+        let client = Client::new("secret")
+            .with_transport(Canned::new(&["HTTP/1.1 304 Not Modified\r\n\r\n"]));
+        assert!(client.get_input(2015, 1).is_err());
+    }
+
+    #[test]
+    fn validate_session() {
+        let events = html_response(crate::account::tests::EVENTS);
+        let canned = Canned::new(&[&events]);
+        let client = Client::new("secret").with_transport(canned.clone());
+        let account = client.validate_session().unwrap();
+        assert_eq!(account.name, "Nicholas & Co");
+        assert_eq!(account.total_stars(), 57);
+        assert!(canned.requests()[0].contains("/events HTTP/1.1\r\n"));
+
+        let logged_out = html_response(crate::account::tests::LOGGED_OUT);
+        let client = Client::new("expired").with_transport(Canned::new(&[&logged_out]));
+        let err = client.validate_session().unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ClientError>(),
+            Some(ClientError::InvalidSession)
+        ));
+    }
 }
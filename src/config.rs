@@ -0,0 +1,95 @@
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+pub const AOC_CONFIG_ENV: &str = "AOC_CONFIG";
+
+/// Settings from `config.toml`. Command line flags take precedence over
+/// environment variables, which take precedence over the config file.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    pub session: Option<String>,
+    pub base_url: Option<String>,
+    pub year: Option<i32>,
+    pub cache_dir: Option<PathBuf>,
+    pub answers_dir: Option<PathBuf>,
+    pub leaderboard: Option<u64>,
+    pub format: Option<String>,
+    pub show_time: Option<bool>,
+    pub jobs: Option<usize>,
+}
+
+impl Config {
+    /// Uses `$AOC_CONFIG` if set, and `$XDG_CONFIG_HOME/aoc/config.toml` or
+    /// `~/.config/aoc/config.toml` otherwise.
+    pub fn path() -> Option<PathBuf> {
+        env::var_os(AOC_CONFIG_ENV)
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("XDG_CONFIG_HOME").map(|dir| Path::new(&dir).join("aoc/config.toml"))
+            })
+            .or_else(|| {
+                env::var_os("HOME").map(|dir| Path::new(&dir).join(".config/aoc/config.toml"))
+            })
+    }
+
+    pub fn from_env() -> Result<Config, ConfigError> {
+        match Config::path() {
+            Some(path) => Config::load(&path),
+            None => Ok(Config::default()),
+        }
+    }
+
+    /// Loads a config file, where a missing file is an empty config.
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Config::default()),
+            Err(err) => return Err(ConfigError::Read(path.display().to_string(), err)),
+        };
+        toml::from_str(&contents).map_err(|err| ConfigError::Parse(path.display().to_string(), err))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("failed to read config {0}: {1}")]
+    Read(String, std::io::Error),
+    #[error("invalid config {0}: {1}")]
+    Parse(String, toml::de::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::tests::temp_dir;
+
+    #[test]
+    fn load() {
+        let dir = temp_dir();
+        let path = dir.join("config.toml");
+        assert_eq!(Config::load(&path).unwrap(), Config::default());
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            &path,
+            "session = \"secret\"\nyear = 2022\ncache-dir = \"/tmp/aoc\"\nshow-time = true\n",
+        )
+        .unwrap();
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.session.as_deref(), Some("secret"));
+        assert_eq!(config.year, Some(2022));
+        assert_eq!(config.cache_dir, Some(PathBuf::from("/tmp/aoc")));
+        assert_eq!(config.show_time, Some(true));
+        assert_eq!(config.format, None);
+
+        fs::write(&path, "sesion = \"typo\"\n").unwrap();
+        assert!(Config::load(&path)
+            .unwrap_err()
+            .to_string()
+            .contains("unknown field `sesion`"));
+    }
+}
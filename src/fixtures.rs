@@ -0,0 +1,92 @@
+use failure::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const FIXTURES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures");
+
+/// Loads a sample input fixture for a day's tests from
+/// `fixtures/{year}/{day}.txt`, or `fixtures/{year}/{day}-{name}.txt` when
+/// a day has several samples.
+#[allow(unused_macros)]
+macro_rules! sample {
+    ($year:literal, $day:literal) => {
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/fixtures/",
+            $year,
+            "/",
+            $day,
+            ".txt"
+        ))
+    };
+    ($year:literal, $day:literal, $name:literal) => {
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/fixtures/",
+            $year,
+            "/",
+            $day,
+            "-",
+            $name,
+            ".txt"
+        ))
+    };
+}
+
+pub fn fixture_path(dir: &Path, year: i32, day: i32, name: Option<&str>) -> PathBuf {
+    let file = match name {
+        Some(name) => format!("{}-{}.txt", day, name),
+        None => format!("{}.txt", day),
+    };
+    dir.join(year.to_string()).join(file)
+}
+
+/// Writes a sample fixture, refusing to replace an existing one.
+pub fn write_fixture(path: &Path, sample: &str) -> Result<(), Error> {
+    if path.exists() {
+        return Err(FixtureError::Exists(path.display().to_string()).into());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, sample)?;
+    Ok(())
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FixtureError {
+    #[error("fixture {0} already exists")]
+    Exists(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::tests::temp_dir;
+
+    #[test]
+    fn paths() {
+        let dir = Path::new("fixtures");
+        assert_eq!(
+            fixture_path(dir, 2023, 5, None),
+            Path::new("fixtures/2023/5.txt")
+        );
+        assert_eq!(
+            fixture_path(dir, 2023, 5, Some("part2")),
+            Path::new("fixtures/2023/5-part2.txt")
+        );
+    }
+
+    #[test]
+    fn write_once() {
+        let path = fixture_path(&temp_dir(), 2023, 1, None);
+        write_fixture(&path, "1abc2\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1abc2\n");
+        assert!(write_fixture(&path, "other\n").is_err());
+    }
+
+    #[test]
+    fn sample_macro() {
+        assert!(sample!(2023, 14).starts_with("O....#....\n"));
+    }
+}
@@ -0,0 +1,180 @@
+use crate::answer::Answer;
+use crate::runner::{Failure, Outcome, Run};
+use serde::Serialize;
+use std::str::FromStr;
+
+/// How runner results are printed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("unknown format {}", s)),
+        }
+    }
+}
+
+/// The result of one puzzle part.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Record {
+    pub year: i32,
+    pub day: i32,
+    pub part: u8,
+    pub answer: Option<String>,
+    /// CPU time of the part in seconds.
+    pub duration: f64,
+    pub status: &'static str,
+    pub error: Option<String>,
+    /// Whether the answer matched the recorded one, when verifying.
+    pub verified: Option<bool>,
+}
+
+/// Flattens a run into one record per part. Parts that never ran share the
+/// status of the parse or input failure that stopped them.
+pub fn records(run: &Run) -> Vec<Record> {
+    let record = |part: u8, answer: Option<&Answer>, status, error: Option<String>| Record {
+        year: run.year,
+        day: run.day,
+        part,
+        answer: answer.map(ToString::to_string),
+        duration: run.times[usize::from(part)].as_secs_f64(),
+        status,
+        error,
+        verified: None,
+    };
+    match &run.outcome {
+        Outcome::Solved(..) => run
+            .parts()
+            .into_iter()
+            .flatten()
+            .map(|(part, result)| match result {
+                Ok(answer) => record(part, Some(answer), "ok", None),
+                Err(failure) => record(part, None, status(failure), Some(failure.to_string())),
+            })
+            .collect(),
+        Outcome::Unparsed(failure) => (1..=2)
+            .map(|part| {
+                let error = format!("parsing {}", failure);
+                record(part, None, status(failure), Some(error))
+            })
+            .collect(),
+        Outcome::Failed(reason) => (1..=2)
+            .map(|part| record(part, None, "failed", Some(reason.clone())))
+            .collect(),
+    }
+}
+
+fn status(failure: &Failure) -> &'static str {
+    match failure {
+        Failure::Panicked(_) => "panicked",
+        Failure::TimedOut(_) => "timed_out",
+        Failure::Skipped => "skipped",
+    }
+}
+
+pub fn to_json(records: &[Record]) -> String {
+    serde_json::to_string_pretty(records).expect("failed to serialize records")
+}
+
+/// Formats records as RFC 4180 CSV, quoting fields such as multi-line
+/// answers that contain separators, quotes or line breaks.
+pub fn to_csv(records: &[Record]) -> String {
+    let mut csv = "year,day,part,answer,duration,status,error,verified\r\n".to_string();
+    for record in records {
+        let fields = [
+            record.year.to_string(),
+            record.day.to_string(),
+            record.part.to_string(),
+            record.answer.as_deref().map(csv_field).unwrap_or_default(),
+            format!("{:.6}", record.duration),
+            record.status.to_string(),
+            record.error.as_deref().map(csv_field).unwrap_or_default(),
+            record.verified.map(|v| v.to_string()).unwrap_or_default(),
+        ];
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+    use crate::runner::Panic;
+    use std::time::Duration;
+
+    fn run() -> Run {
+        Run {
+            year: 2022,
+            day: 10,
+            outcome: Outcome::Solved(
+                Ok(Answer::Integer(13140)),
+                Err(Failure::Panicked(Panic {
+                    message: "bad \"crt\"".to_string(),
+                    location: None,
+                })),
+            ),
+            times: [
+                Duration::from_millis(1),
+                Duration::from_millis(2),
+                Duration::from_millis(3),
+            ],
+        }
+    }
+
+    #[test]
+    fn run_records() {
+        let parts = records(&run());
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].answer.as_deref(), Some("13140"));
+        assert_eq!(parts[0].status, "ok");
+        assert_eq!(parts[0].duration, 0.002);
+        assert_eq!(parts[1].status, "panicked");
+        assert_eq!(parts[1].error.as_deref(), Some("panicked: bad \"crt\""));
+
+        let failed = records(&Run::failed(2022, 11, "offline".to_string()));
+        assert_eq!(failed[1].status, "failed");
+        assert_eq!(failed[1].error.as_deref(), Some("offline"));
+    }
+
+    #[test]
+    fn csv_escapes_multi_line_answers() {
+        let mut records = records(&run());
+        records[0].answer = Some("\n##..\n..##".to_string());
+        records[0].verified = Some(true);
+        assert_eq!(
+            to_csv(&records),
+            "year,day,part,answer,duration,status,error,verified\r\n\
+             2022,10,1,\"\n##..\n..##\",0.002000,ok,,true\r\n\
+             2022,10,2,,0.003000,panicked,\"panicked: bad \"\"crt\"\"\",\r\n"
+        );
+    }
+
+    #[test]
+    fn json_escapes_multi_line_answers() {
+        let mut records = records(&run());
+        records[0].answer = Some("\n##..\n..##".to_string());
+        let json = to_json(&records[..1]);
+        assert!(json.contains(r#""answer": "\n##..\n..##","#));
+        assert!(json.contains(r#""verified": null"#));
+    }
+}
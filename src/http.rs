@@ -0,0 +1,326 @@
+use failure::Error;
+use native_tls::{HandshakeError, TlsConnector};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::io::{BufRead, Read, Write};
+use std::net::TcpStream;
+use std::str::FromStr;
+use std::sync::Arc;
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+/// The scheme, host and port requests are sent to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Origin {
+    pub tls: bool,
+    pub host: String,
+    pub port: u16,
+}
+
+impl Origin {
+    /// The value of the `Host` header, which leaves out default ports.
+    pub fn authority(&self) -> String {
+        match (self.tls, self.port) {
+            (true, 443) | (false, 80) => self.host.clone(),
+            _ => format!("{}:{}", self.host, self.port),
+        }
+    }
+
+    /// Plain TCP for `http` and TLS for `https` origins.
+    pub fn transport(&self) -> Arc<dyn Transport> {
+        if self.tls {
+            Arc::new(Tls)
+        } else {
+            Arc::new(Tcp)
+        }
+    }
+}
+
+impl FromStr for Origin {
+    type Err = HttpError;
+
+    fn from_str(url: &str) -> Result<Origin, HttpError> {
+        let invalid = || HttpError::InvalidUrl(url.to_owned());
+        let (tls, rest) = match url.split_once("://") {
+            Some(("https", rest)) => (true, rest),
+            Some(("http", rest)) => (false, rest),
+            _ => return Err(invalid()),
+        };
+        let authority = rest.strip_suffix('/').unwrap_or(rest);
+        if authority.contains('/') {
+            return Err(invalid());
+        }
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().map_err(|_| invalid())?),
+            None => (authority, if tls { 443 } else { 80 }),
+        };
+        if host.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Origin {
+            tls,
+            host: host.to_owned(),
+            port,
+        })
+    }
+}
+
+impl Display for Origin {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let scheme = if self.tls { "https" } else { "http" };
+        write!(f, "{}://{}", scheme, self.authority())
+    }
+}
+
+pub trait Stream: Read + Write {}
+
+impl<T: Read + Write> Stream for T {}
+
+/// Opens the connection for a single request.
+pub trait Transport: Debug + Send + Sync {
+    fn connect(&self, host: &str, port: u16) -> Result<Box<dyn Stream>, Error>;
+}
+
+/// Unencrypted HTTP, for testing against a local server.
+#[derive(Debug)]
+pub struct Tcp;
+
+impl Transport for Tcp {
+    fn connect(&self, host: &str, port: u16) -> Result<Box<dyn Stream>, Error> {
+        Ok(Box::new(TcpStream::connect((host, port))?))
+    }
+}
+
+#[derive(Debug)]
+pub struct Tls;
+
+impl Transport for Tls {
+    fn connect(&self, host: &str, port: u16) -> Result<Box<dyn Stream>, Error> {
+        let connector = TlsConnector::new()?;
+        let stream = TcpStream::connect((host, port))?;
+        let stream = connector.connect(host, stream).map_err(|err| match err {
+            HandshakeError::Failure(err) => err,
+            HandshakeError::WouldBlock(_) => unreachable!("handshake on a blocking stream"),
+        })?;
+        Ok(Box::new(stream))
+    }
+}
+
+/// An HTTP/1.1 response with its body fully read.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// Reads a response to a request with the given method. The body is
+    /// delimited by chunked transfer encoding, by `Content-Length`, or
+    /// otherwise by the end of the stream.
+    pub fn read<R: BufRead>(mut reader: R, method: &str) -> Result<Response, Error> {
+        let line = read_line(&mut reader)?;
+        let mut parts = line.splitn(3, ' ');
+        let status = match (parts.next(), parts.next()) {
+            (Some(version), Some(status)) if version.starts_with("HTTP/1.") => {
+                status.parse().map_err(|_| HttpError::InvalidStatusLine)?
+            }
+            _ => return Err(HttpError::InvalidStatusLine.into()),
+        };
+
+        let mut headers = Vec::new();
+        loop {
+            let line = read_line(&mut reader)?;
+            if line.is_empty() {
+                break;
+            }
+            match line.split_once(':') {
+                Some((name, value)) => {
+                    headers.push((name.trim().to_owned(), value.trim().to_owned()))
+                }
+                None => return Err(HttpError::InvalidHeader.into()),
+            }
+        }
+
+        let mut response = Response {
+            status,
+            headers,
+            body: Vec::new(),
+        };
+        if method == "HEAD" || status / 100 == 1 || status == 204 || status == 304 {
+            return Ok(response);
+        }
+
+        // Chunked has to be the final encoding when present.
+        let chunked = response
+            .header("Transfer-Encoding")
+            .is_some_and(|encoding| {
+                let last = encoding.rsplit(',').next().unwrap_or_default();
+                last.trim().eq_ignore_ascii_case("chunked")
+            });
+        response.body = if chunked {
+            read_chunked(&mut reader)?
+        } else if let Some(length) = response.header("Content-Length") {
+            let length = length.parse().map_err(|_| HttpError::InvalidHeader)?;
+            let mut body = vec![0; length];
+            reader
+                .read_exact(&mut body)
+                .map_err(|_| HttpError::UnexpectedEndOfStream)?;
+            body
+        } else {
+            let mut body = Vec::new();
+            reader.read_to_end(&mut body)?;
+            body
+        };
+        Ok(response)
+    }
+
+    /// Looks up a header by case-insensitive name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn is_redirect(&self) -> bool {
+        matches!(self.status, 301 | 302 | 303 | 307 | 308)
+    }
+
+    pub fn text(self) -> Result<String, Error> {
+        Ok(String::from_utf8(self.body)?)
+    }
+}
+
+/// Reads a line without its `\r\n` or `\n` terminator.
+fn read_line<R: BufRead>(reader: &mut R) -> Result<String, Error> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(HttpError::UnexpectedEndOfStream.into());
+    }
+    let len = line.trim_end_matches(&['\r', '\n'][..]).len();
+    line.truncate(len);
+    Ok(line)
+}
+
+fn read_chunked<R: BufRead>(reader: &mut R) -> Result<Vec<u8>, Error> {
+    let mut body = Vec::new();
+    loop {
+        let line = read_line(reader)?;
+        let size = line.split(';').next().unwrap_or("").trim();
+        let size = usize::from_str_radix(size, 16).map_err(|_| HttpError::InvalidChunk)?;
+        if size == 0 {
+            break;
+        }
+
+        let start = body.len();
+        body.resize(start + size, 0);
+        reader
+            .read_exact(&mut body[start..])
+            .map_err(|_| HttpError::UnexpectedEndOfStream)?;
+        if !read_line(reader)?.is_empty() {
+            return Err(HttpError::InvalidChunk.into());
+        }
+    }
+
+    // Skip trailer fields up to the final empty line.
+    while !read_line(reader)?.is_empty() {}
+    Ok(body)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum HttpError {
+    #[error("unexpected end of stream")]
+    UnexpectedEndOfStream,
+    #[error("invalid HTTP status line format")]
+    InvalidStatusLine,
+    #[error("invalid HTTP header format")]
+    InvalidHeader,
+    #[error("invalid chunk in chunked response body")]
+    InvalidChunk,
+    #[error("too many redirects")]
+    TooManyRedirects,
+    #[error("invalid base URL {0}, expected e.g. `https://adventofcode.com`")]
+    InvalidUrl(String),
+    #[error("refusing to follow redirect to {0}")]
+    ForeignRedirect(String),
+    #[error("HTTP error code {0}")]
+    StatusCode(i32),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(response: &str) -> Result<Response, Error> {
+        Response::read(response.as_bytes(), "GET")
+    }
+
+    #[test]
+    fn content_length() {
+        let response =
+            read("HTTP/1.1 200 OK\r\ncontent-length: 5\r\nX-Other: a:b\r\n\r\n1 2 3 trailing")
+                .unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.header("Content-Length"), Some("5"));
+        assert_eq!(response.header("x-other"), Some("a:b"));
+        assert_eq!(response.text().unwrap(), "1 2 3");
+
+        assert!(read("HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nshort").is_err());
+    }
+
+    #[test]
+    fn chunked() {
+        let response = read(
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+             4\r\n1 2 \r\n7;ext=1\r\n3\n4 5 6\r\n0\r\nExpires: never\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(response.text().unwrap(), "1 2 3\n4 5 6");
+
+        assert!(read("HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nz\r\n").is_err());
+        assert!(read("HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n1 2").is_err());
+    }
+
+    #[test]
+    fn read_to_end() {
+        let response = read("HTTP/1.0 200 OK\nConnection: close\n\nline 1\nline 2\n").unwrap();
+        assert_eq!(response.text().unwrap(), "line 1\nline 2\n");
+    }
+
+    #[test]
+    fn no_body() {
+        let response = read("HTTP/1.1 304 Not Modified\r\nContent-Length: 5\r\n\r\n").unwrap();
+        assert!(response.body.is_empty());
+
+        let response =
+            read("HTTP/1.1 302 Found\r\nLocation: /2023/day/1\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+        assert!(response.is_redirect());
+        assert_eq!(response.header("location"), Some("/2023/day/1"));
+    }
+
+    #[test]
+    fn origins() {
+        let origin = DEFAULT_BASE_URL.parse::<Origin>().unwrap();
+        assert_eq!((origin.tls, origin.port), (true, 443));
+        assert_eq!(origin.authority(), "adventofcode.com");
+        assert_eq!(origin.to_string(), DEFAULT_BASE_URL);
+
+        let origin = "http://127.0.0.1:8080/".parse::<Origin>().unwrap();
+        assert_eq!((origin.tls, origin.port), (false, 8080));
+        assert_eq!(origin.to_string(), "http://127.0.0.1:8080");
+
+        assert!("adventofcode.com".parse::<Origin>().is_err());
+        assert!("ftp://adventofcode.com".parse::<Origin>().is_err());
+        assert!("https://adventofcode.com/2023".parse::<Origin>().is_err());
+        assert!("http://localhost:port".parse::<Origin>().is_err());
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(read("").is_err());
+        assert!(read("garbage\r\n\r\n").is_err());
+        assert!(read("HTTP/1.1 abc OK\r\n\r\n").is_err());
+        assert!(read("HTTP/1.1 200 OK\r\nno colon\r\n\r\n").is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use failure::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+/// A local puzzle input given on the command line instead of fetching it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Source {
+    Stdin,
+    File(PathBuf),
+}
+
+impl Source {
+    /// Parses a command line path, where `-` stands for stdin.
+    pub fn new(path: &str) -> Source {
+        match path {
+            "-" => Source::Stdin,
+            path => Source::File(path.into()),
+        }
+    }
+
+    pub fn read(&self) -> Result<String, Error> {
+        let input = match self {
+            Source::Stdin => {
+                let mut input = String::new();
+                io::stdin().read_to_string(&mut input)?;
+                input
+            }
+            Source::File(path) => fs::read_to_string(path)?,
+        };
+        Ok(input)
+    }
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Source::Stdin => write!(f, "stdin"),
+            Source::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::tests::temp_dir;
+
+    #[test]
+    fn sources() {
+        assert_eq!(Source::new("-"), Source::Stdin);
+        assert_eq!(Source::new("in.txt"), Source::File("in.txt".into()));
+        assert_eq!(Source::new("in.txt").to_string(), "in.txt");
+    }
+
+    #[test]
+    fn read_file() {
+        let dir = temp_dir();
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("input.txt");
+        fs::write(&path, "1\n2\n").unwrap();
+        assert_eq!(Source::File(path).read().unwrap(), "1\n2\n");
+    }
+}
@@ -0,0 +1,222 @@
+use crate::calendar;
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use failure::Error;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+pub const AOC_LEADERBOARD_ENV: &str = "AOC_LEADERBOARD";
+
+/// A private leaderboard as served by
+/// `/{year}/leaderboard/private/view/{id}.json`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Leaderboard {
+    pub event: String,
+    pub members: HashMap<String, Member>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Member {
+    pub id: u64,
+    pub name: Option<String>,
+    pub stars: u32,
+    pub local_score: u32,
+    pub last_star_ts: i64,
+    /// Star timestamps by day and part.
+    #[serde(default)]
+    pub completion_day_level: BTreeMap<String, BTreeMap<String, Star>>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Star {
+    pub get_star_ts: i64,
+}
+
+impl Leaderboard {
+    pub fn parse(json: &str) -> Result<Leaderboard, Error> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn year(&self) -> i32 {
+        self.event.parse().unwrap_or_default()
+    }
+
+    /// Members by descending local score, breaking ties by stars and then by
+    /// who got their last star first.
+    pub fn ranked(&self) -> Vec<&Member> {
+        let mut members = self.members.values().collect::<Vec<_>>();
+        members.sort_by_key(|member| {
+            (
+                std::cmp::Reverse(member.local_score),
+                std::cmp::Reverse(member.stars),
+                member.last_star_ts,
+                member.id,
+            )
+        });
+        members
+    }
+
+    /// Renders the ranked table with a star column per day.
+    pub fn render(&self, color: bool) -> String {
+        let days = calendar::days_in_event(self.year());
+        let indent = " ".repeat(16);
+        let tens = (1..=days)
+            .map(|day| if day < 10 { ' ' } else { digit(day / 10) })
+            .collect::<String>();
+        let units = (1..=days).map(|day| digit(day % 10)).collect::<String>();
+
+        let mut lines = vec![
+            format!("{}{}", indent, tens).trim_end().to_string(),
+            format!("{}{}", indent, units),
+        ];
+        for (rank, member) in self.ranked().into_iter().enumerate() {
+            let stars = (1..=days)
+                .map(|day| {
+                    let (star, style): (_, fn(&str) -> String) = match member.stars_on(day) {
+                        2 => ("*", |s| s.yellow().to_string()),
+                        1 => ("+", |s| s.white().to_string()),
+                        _ => (".", |s| s.bright_black().to_string()),
+                    };
+                    if color {
+                        style(star)
+                    } else {
+                        star.to_string()
+                    }
+                })
+                .collect::<String>();
+            let last = match timestamp(member.last_star_ts) {
+                Some(last) => last.format("%Y-%m-%d %H:%M:%S").to_string(),
+                None => "-".to_string(),
+            };
+            lines.push(format!(
+                "{:>3}) {:>5} {:>3}* {}  {:<19}  {}",
+                rank + 1,
+                member.local_score,
+                member.stars,
+                stars,
+                last,
+                member.display_name()
+            ));
+        }
+        lines.join("\n")
+    }
+
+    /// Renders when each member completed the parts of a day, relative to
+    /// the puzzle unlock, or `None` if nobody has a star yet.
+    pub fn render_day(&self, day: i32) -> Option<String> {
+        let mut members = self
+            .members
+            .values()
+            .filter(|member| member.stars_on(day) > 0)
+            .collect::<Vec<_>>();
+        if members.is_empty() {
+            return None;
+        }
+        let unlock = calendar::unlock_time(self.year(), day);
+        members.sort_by_key(|member| {
+            let part2 = member.star(day, 2).unwrap_or(i64::MAX);
+            (part2, member.star(day, 1), member.id)
+        });
+
+        let elapsed = |ts: Option<i64>| match ts.and_then(timestamp) {
+            Some(ts) => calendar::format_duration(ts - unlock),
+            None => "-".to_string(),
+        };
+        let mut lines = vec![format!("     {:>14} {:>14}", "Part 1", "Part 2")];
+        lines.extend(members.into_iter().enumerate().map(|(rank, member)| {
+            format!(
+                "{:>3}) {:>14} {:>14}  {}",
+                rank + 1,
+                elapsed(member.star(day, 1)),
+                elapsed(member.star(day, 2)),
+                member.display_name()
+            )
+        }));
+        Some(lines.join("\n"))
+    }
+}
+
+impl Member {
+    /// Members without a public name show up as anonymous on the site.
+    pub fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("(anonymous user #{})", self.id),
+        }
+    }
+
+    pub fn star(&self, day: i32, part: u8) -> Option<i64> {
+        let parts = self.completion_day_level.get(&day.to_string())?;
+        Some(parts.get(&part.to_string())?.get_star_ts)
+    }
+
+    pub fn stars_on(&self, day: i32) -> usize {
+        (1..=2)
+            .filter(|&part| self.star(day, part).is_some())
+            .count()
+    }
+}
+
+fn digit(n: i32) -> char {
+    std::char::from_digit(n as u32, 10).expect("single digit")
+}
+
+/// Converts a timestamp, where 0 means there is none.
+fn timestamp(ts: i64) -> Option<DateTime<Utc>> {
+    if ts > 0 {
+        DateTime::from_timestamp(ts, 0)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    pub const LEADERBOARD: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/fixtures/leaderboard/2023.json"
+    ));
+
+    #[test]
+    fn parse_fixture() {
+        let leaderboard = Leaderboard::parse(LEADERBOARD).unwrap();
+        assert_eq!(leaderboard.year(), 2023);
+        assert_eq!(leaderboard.members.len(), 4);
+
+        let ranked = leaderboard
+            .ranked()
+            .into_iter()
+            .map(|member| member.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ranked, vec![1001, 1003, 1002, 1004]);
+    }
+
+    #[test]
+    fn render_table() {
+        let leaderboard = Leaderboard::parse(LEADERBOARD).unwrap();
+        assert_eq!(
+            leaderboard.render(false),
+            "                         1111111111222222
+                1234567890123456789012345
+  1)    26   5* **+......................  2023-12-03 05:30:00  Nicholas Lordello
+  2)    26   4* **.......................  2023-12-02 06:53:20  Ada
+  3)    10   2* *........................  2023-12-01 05:05:00  (anonymous user #1002)
+  4)     0   0* .........................  -                    Idle"
+        );
+    }
+
+    #[test]
+    fn render_day() {
+        let leaderboard = Leaderboard::parse(LEADERBOARD).unwrap();
+        assert_eq!(
+            leaderboard.render_day(1).unwrap(),
+            "             Part 1         Part 2
+  1)         3m 00s         5m 00s  (anonymous user #1002)
+  2)        10m 00s        30m 00s  Nicholas Lordello
+  3)        53m 20s     1h 10m 00s  Ada"
+        );
+        assert_eq!(leaderboard.render_day(4), None);
+    }
+}
@@ -1,4 +1,25 @@
+#[macro_use]
+mod fixtures;
+#[macro_use]
+mod solution;
+
+mod account;
+mod answer;
+mod answers;
+mod bench;
+mod cache;
+mod calendar;
 mod client;
+mod config;
+mod format;
+mod http;
+mod input;
+mod leaderboard;
+mod puzzle;
+mod runner;
+mod scaffold;
+mod status;
+mod throttle;
 mod util;
 
 mod year2015;
@@ -9,192 +30,1072 @@ mod year2020;
 mod year2022;
 mod year2023;
 
-use crate::client::Client;
-use clap::{Arg, ArgAction, Command};
-use std::time::Instant;
+use crate::answer::Answer;
+use crate::answers::{Answers, Check};
+use crate::bench::Budget;
+use crate::cache::Cache;
+use crate::calendar::CalendarError;
+use crate::client::{Client, ClientError, Submission};
+use crate::config::Config;
+use crate::fixtures::FIXTURES_DIR;
+use crate::format::Format;
+use crate::input::Source;
+use crate::runner::{Outcome, Run};
+use crate::solution::{Parser, Registry};
+use crate::status::Status;
+use chrono::Utc;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use failure::Error;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+fn cli() -> Command {
+    Command::new("aoc")
+    .version("0.1")
+    .about("Execute Advent of Code problems")
+    .author("Nicholas Lordello")
+    .arg(
+        Arg::new("year")
+            .short('y')
+            .long("year")
+            .value_name("YEAR")
+            .help("Event year [default: the running event in December, else the last one]")
+            .global(true)
+            .value_parser(clap::value_parser!(i32)),
+    )
+    .arg(
+        Arg::new("show-time")
+            .long("show-time")
+            .help("Show timestamps in output")
+            .overrides_with("no-show-time")
+            .action(ArgAction::SetTrue), // <-- make it a flag
+    )
+    .arg(
+        Arg::new("no-show-time")
+            .long("no-show-time")
+            .help("Hide timestamps even if the config file shows them")
+            .overrides_with("show-time")
+            .action(ArgAction::SetTrue),
+    )
+    .arg(
+        Arg::new("offline")
+            .long("offline")
+            .help("Only use cached inputs and never access the network")
+            .global(true)
+            .action(ArgAction::SetTrue),
+    )
+    .arg(
+        Arg::new("cache-dir")
+            .long("cache-dir")
+            .value_name("DIR")
+            .help("Directory for cached inputs [default: $AOC_CACHE_DIR or ~/.cache/aoc]")
+            .global(true)
+            .value_parser(clap::value_parser!(PathBuf)),
+    )
+    .arg(
+        Arg::new("submit")
+            .long("submit")
+            .value_name("PART")
+            .help("Submit the answer to the given puzzle part")
+            .conflicts_with("all")
+            .value_parser(clap::value_parser!(u8).range(1..=2)),
+    )
+    .arg(
+        Arg::new("bench")
+            .long("bench")
+            .help("Benchmark parsing and both puzzles instead of printing answers")
+            .action(ArgAction::SetTrue),
+    )
+    .arg(
+        Arg::new("runs")
+            .long("runs")
+            .value_name("N")
+            .help("Number of benchmark runs per phase")
+            .default_value("10")
+            .value_parser(clap::value_parser!(usize)),
+    )
+    .arg(
+        Arg::new("bench-time")
+            .long("bench-time")
+            .value_name("SECONDS")
+            .help("Benchmark each phase for a time budget instead of a number of runs")
+            .conflicts_with("runs")
+            .value_parser(parse_seconds),
+    )
+    .arg(
+        Arg::new("json")
+            .long("json")
+            .help("Print benchmark results as JSON")
+            .requires("bench")
+            .action(ArgAction::SetTrue),
+    )
+    .arg(
+        Arg::new("format")
+            .long("format")
+            .value_name("FORMAT")
+            .help(
+                "Print answers as text, or as JSON or CSV records for scripts [default: text]",
+            )
+            .conflicts_with_all(["bench", "submit"])
+            .value_parser(["text", "json", "csv"]),
+    )
+    .arg(
+        Arg::new("all")
+            .long("all")
+            .help("Run all registered days of the year, or of every year if none is given or configured")
+            .conflicts_with("days")
+            .action(ArgAction::SetTrue),
+    )
+    .arg(
+        Arg::new("verify")
+            .long("verify")
+            .help("Check answers against the recorded answers file")
+            .action(ArgAction::SetTrue),
+    )
+    .arg(
+        Arg::new("record")
+            .long("record")
+            .help("Record the answers adventofcode.com accepted that are not yet in the answers file")
+            .action(ArgAction::SetTrue),
+    )
+    .arg(
+        Arg::new("answers-dir")
+            .long("answers-dir")
+            .value_name("DIR")
+            .help("Directory of recorded answers [default: $AOC_ANSWERS_DIR or ./answers]")
+            .value_parser(clap::value_parser!(PathBuf)),
+    )
+    .arg(
+        Arg::new("timeout")
+            .long("timeout")
+            .value_name("SECONDS")
+            .help("Give up on parsing or a puzzle part after this long")
+            .value_parser(parse_seconds),
+    )
+    .arg(
+        Arg::new("jobs")
+            .short('j')
+            .long("jobs")
+            .value_name("N")
+            .help("Fetch and solve up to N days in parallel, 0 for one per CPU [default: 1]")
+            .conflicts_with("bench")
+            .value_parser(clap::value_parser!(usize)),
+    )
+    .arg(
+        Arg::new("wait")
+            .long("wait")
+            .help("Wait for the day to unlock, then fetch its input and solve it [default year: the running or next event]")
+            .conflicts_with_all(["all", "input", "offline", "bench"])
+            .action(ArgAction::SetTrue),
+    )
+    .arg(
+        Arg::new("input")
+            .long("input")
+            .value_name("PATH")
+            .help("Read input from files instead of fetching it, `-` for stdin")
+            .num_args(1..)
+            .conflicts_with_all(["all", "submit", "verify", "record"]),
+    )
+    .arg(
+        Arg::new("days")
+            .short('d')
+            .value_name("DAY")
+            .help("Days to run, where `today` is the latest unlocked day [default: today]")
+            .num_args(1..), // one or more
+    )
+    .subcommand_negates_reqs(true)
+    .subcommand(
+        Command::new("sample")
+            .about("Save an example from the puzzle description as a test fixture")
+            .arg(
+                Arg::new("day")
+                    .short('d')
+                    .long("day")
+                    .value_name("DAY")
+                    .help("Day of the puzzle, or `today` [default: today]"),
+            )
+            .arg(
+                Arg::new("block")
+                    .long("block")
+                    .value_name("N")
+                    .help("Code block to save, prompting with a list if omitted")
+                    .value_parser(clap::value_parser!(usize)),
+            )
+            .arg(
+                Arg::new("name")
+                    .long("name")
+                    .value_name("NAME")
+                    .help("Suffix for days with several samples, e.g. `part2`"),
+            ),
+    )
+    .subcommand(
+        Command::new("new")
+            .about("Create a new day module from the year's day00.rs template")
+            .arg(
+                Arg::new("day")
+                    .short('d')
+                    .long("day")
+                    .value_name("DAY")
+                    .help("Day of the puzzle, or `today` [default: today]"),
+            )
+            .arg(
+                Arg::new("input")
+                    .long("input")
+                    .help("Also download the puzzle input")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("sample")
+                    .long("sample")
+                    .help("Also save an example from the puzzle as a test fixture")
+                    .action(ArgAction::SetTrue),
+            ),
+    )
+    .subcommand(
+        Command::new("describe")
+            .about("Print the puzzle description")
+            .arg(
+                Arg::new("day")
+                    .short('d')
+                    .long("day")
+                    .value_name("DAY")
+                    .help("Day of the puzzle, or `today` [default: today]"),
+            ),
+    )
+    .subcommand(
+        Command::new("leaderboard")
+            .about("Show a private leaderboard")
+            .arg(
+                Arg::new("id")
+                    .long("id")
+                    .value_name("ID")
+                    .help("Leaderboard ID, the owner's user ID [default: $AOC_LEADERBOARD]")
+                    .value_parser(clap::value_parser!(u64)),
+            )
+            .arg(
+                Arg::new("day")
+                    .short('d')
+                    .long("day")
+                    .value_name("DAY")
+                    .help("Show completion times for a day, or `today`"),
+            ),
+    )
+    .subcommand(
+        Command::new("whoami")
+            .about("Show the account the session belongs to and its stars per event"),
+    )
+    .subcommand(
+        Command::new("status")
+            .about("Show which days are implemented by running them on cached inputs")
+            .arg(
+                Arg::new("timeout")
+                    .long("timeout")
+                    .value_name("SECONDS")
+                    .help("Time to give each part before counting it as implemented")
+                    .default_value("1")
+                    .value_parser(parse_seconds),
+            ),
+    )
+}
 
 fn main() {
-    let matches = Command::new("aoc")
-        .version("0.1")
-        .about("Execute Advent of Code problems")
-        .author("Nicholas Lordello")
-        .arg(
-            Arg::new("year")
-                .short('y')
-                .long("year")
-                .value_name("YEAR")
-                .default_value("2016")
-                .value_parser(clap::value_parser!(i32)),
-        )
-        .arg(
-            Arg::new("show-time")
-                .long("show-time")
-                .help("Show timestamps in output")
-                .action(ArgAction::SetTrue), // <-- make it a flag
-        )
-        .arg(
-            Arg::new("days")
-                .short('d')
-                .value_name("DAY")
-                .required(true)
-                .num_args(1..) // one or more
-                .value_parser(clap::value_parser!(i32)),
-        )
-        .get_matches();
-
-    let year: i32 = *matches.get_one::<i32>("year").expect("year is required");
-    let show_time: bool = matches.get_flag("show-time");
-    let days: Vec<i32> = matches
-        .get_many::<i32>("days")
-        .unwrap_or_default()
+    let matches = cli().get_matches();
+    let config = Config::from_env().unwrap_or_else(|err| {
+        eprintln!("error: {}", err);
+        process::exit(2);
+    });
+
+    match matches.subcommand() {
+        Some(("sample", matches)) => sample_command(matches, &config),
+        Some(("new", matches)) => new_command(matches, &config),
+        Some(("describe", matches)) => describe_command(matches, &config),
+        Some(("whoami", matches)) => whoami_command(matches, &config),
+        Some(("leaderboard", matches)) => leaderboard_command(matches, &config),
+        Some(("status", matches)) => status_command(matches, &config),
+        _ => run_command(&matches, &config),
+    }
+}
+
+fn sample_command(matches: &ArgMatches, config: &Config) {
+    let year = year(matches, config);
+    let day = day(matches, year, "day");
+    let sample = fetch_sample(
+        &client(matches, config),
+        year,
+        day,
+        matches.get_one::<usize>("block").copied(),
+    );
+
+    let name = matches.get_one::<String>("name").map(String::as_str);
+    let path = fixtures::fixture_path(Path::new(FIXTURES_DIR), year, day, name);
+    write_fixture(&path, &sample);
+    println!("wrote {}", path.display());
+}
+
+fn new_command(matches: &ArgMatches, config: &Config) {
+    let year = year(matches, config);
+    let day = day(matches, year, "day");
+    let client = client(matches, config);
+    let src = Path::new(scaffold::SRC_DIR);
+    let sample = matches.get_flag("sample");
+    if let Err(err) = scaffold::check_new_day(src, year, day, sample) {
+        eprintln!("error: {}", err);
+        process::exit(2);
+    }
+    let sample = if sample {
+        Some(fetch_sample(&client, year, day, None))
+    } else {
+        None
+    };
+
+    let path = scaffold::new_day(src, year, day, sample.is_some()).unwrap_or_else(|err| {
+        eprintln!("error: failed to create {} day {}: {}", year, day, err);
+        process::exit(2);
+    });
+    println!("wrote {}", path.display());
+
+    if let Some(sample) = sample {
+        let path = fixtures::fixture_path(Path::new(FIXTURES_DIR), year, day, None);
+        write_fixture(&path, &sample);
+        println!("wrote {}", path.display());
+    }
+    if matches.get_flag("input") {
+        if let Err(err) = client.get_input(year, day) {
+            fail(&format!("input for {} day {}", year, day), err);
+        }
+        println!("downloaded input for {} day {}", year, day);
+    }
+}
+
+fn describe_command(matches: &ArgMatches, config: &Config) {
+    let year = year(matches, config);
+    let day = day(matches, year, "day");
+    let puzzle = client(matches, config)
+        .get_puzzle(year, day)
+        .unwrap_or_else(|err| fail(&format!("puzzle for {} day {}", year, day), err));
+    println!("{}", puzzle.render(io::stdout().is_terminal()));
+}
+
+fn whoami_command(matches: &ArgMatches, config: &Config) {
+    let account = client(matches, config)
+        .validate_session()
+        .unwrap_or_else(|err| fail("account", err));
+    println!("{}", account.render());
+}
+
+fn leaderboard_command(matches: &ArgMatches, config: &Config) {
+    let year = year(matches, config);
+    let id = matches.get_one::<u64>("id").copied().unwrap_or_else(|| {
+        env::var(leaderboard::AOC_LEADERBOARD_ENV)
+            .ok()
+            .and_then(|id| id.parse().ok())
+            .or(config.leaderboard)
+            .unwrap_or_else(|| {
+                eprintln!(
+                    "error: pass --id, set ${} or `leaderboard` in the config file",
+                    leaderboard::AOC_LEADERBOARD_ENV
+                );
+                process::exit(2);
+            })
+    });
+    let day = matches
+        .contains_id("day")
+        .then(|| day(matches, year, "day"));
+    let leaderboard = client(matches, config)
+        .get_leaderboard(year, id)
+        .unwrap_or_else(|err| fail(&format!("leaderboard {} for {}", id, year), err));
+    if let Some(day) = day {
+        match leaderboard.render_day(day) {
+            Some(table) => println!("{}", table),
+            None => println!("nobody has a star on day {} yet", day),
+        }
+    } else {
+        println!("{}", leaderboard.render(io::stdout().is_terminal()));
+    }
+}
+
+fn status_command(matches: &ArgMatches, config: &Config) {
+    let registry = registry();
+    let years = match matches.get_one::<i32>("year") {
+        Some(&year) => vec![year],
+        None => (calendar::FIRST_YEAR..=calendar::default_year(Utc::now())).collect(),
+    };
+    let timeout = *matches
+        .get_one::<Duration>("timeout")
+        .expect("timeout has default");
+
+    // Stubs are told apart by running days on their cached inputs, and
+    // days are never fetched just for this. Days run one at a time so
+    // that which ones time out doesn't depend on what else is running.
+    let client = Client::offline(cache(matches, config));
+    let days = registry
+        .days()
+        .filter(|(year, _)| years.contains(year))
+        .collect::<Vec<_>>();
+    let mut statuses = BTreeMap::new();
+    runner::parallel(
+        1,
+        &days,
+        |&(year, day)| match (client.get_input(year, day), solution(&registry, year, day)) {
+            (Ok(input), Some(parse)) => {
+                Status::of(&Run::solve(year, day, parse, &input, Some(timeout)))
+            }
+            _ => Status::Registered,
+        },
+        |&day, status| {
+            statuses.insert(day, status);
+        },
+    );
+    println!(
+        "{}",
+        status::render(&years, &statuses, io::stdout().is_terminal())
+    );
+}
+
+/// Fetches and solves the selected days, then prints, checks and submits
+/// their answers.
+fn run_command(matches: &ArgMatches, config: &Config) {
+    let wait = matches.get_flag("wait");
+    let year: i32 = if wait {
+        // Before December the puzzle to wait for is in the coming event.
+        matches
+            .get_one::<i32>("year")
+            .copied()
+            .or(config.year)
+            .unwrap_or_else(|| calendar::upcoming_year(Utc::now()))
+    } else {
+        year(matches, config)
+    };
+    let show_time: bool = matches.get_flag("show-time")
+        || !matches.get_flag("no-show-time") && config.show_time.unwrap_or(false);
+    let submit: Option<u8> = matches.get_one::<u8>("submit").copied();
+    let budget = match matches.get_one::<Duration>("bench-time") {
+        Some(&time) => Budget::Time(time),
+        None => Budget::Runs(*matches.get_one::<usize>("runs").expect("runs has default")),
+    };
+    let bench = matches.get_flag("bench");
+    let timeout = matches.get_one::<Duration>("timeout").copied();
+    let jobs = match matches
+        .get_one::<usize>("jobs")
         .copied()
+        .or(config.jobs)
+        .unwrap_or(1)
+    {
+        _ if bench => 1,
+        0 => thread::available_parallelism().map_or(1, usize::from),
+        jobs => jobs,
+    };
+    let json = matches.get_flag("json");
+    let format: Format = match matches
+        .get_one::<String>("format")
+        .or(config.format.as_ref())
+    {
+        Some(_) if bench || submit.is_some() => Format::Text,
+        Some(format) => format.parse().unwrap_or_else(|err| {
+            eprintln!("error: {}", err);
+            process::exit(2);
+        }),
+        None => Format::Text,
+    };
+    let recorded = match matches.get_one::<PathBuf>("answers-dir") {
+        Some(dir) => Answers::new(dir),
+        None => Answers::from_env(config.answers_dir.as_deref()),
+    };
+    let all = matches.get_flag("all");
+    let registry = registry();
+    let days: Vec<(i32, i32)> = if all {
+        let explicit_year = matches.contains_id("year") || config.year.is_some();
+        registry
+            .days()
+            .filter(|&(y, _)| !explicit_year || y == year)
+            .collect()
+    } else {
+        match matches.get_many::<String>("days") {
+            Some(days) => days.map(|day| (year, parse_day(year, day))).collect(),
+            None => vec![(year, parse_day(year, "today"))],
+        }
+    };
+
+    let sources: Vec<Source> = matches
+        .get_many::<String>("input")
+        .unwrap_or_default()
+        .map(|path| Source::new(path))
         .collect();
+    if !sources.is_empty() && days.len() != 1 {
+        eprintln!("error: --input can only be used with a single day");
+        process::exit(2);
+    }
+    let inputs = sources
+        .iter()
+        .map(|source| {
+            let input = source
+                .read()
+                .unwrap_or_else(|err| panic!("failed to read input from {}: {}", source, err));
+            (Some(source.to_string()), input)
+        })
+        .collect::<Vec<_>>();
 
-    let client = Client::from_env().expect("failed to create adventofcode.com client");
-    for day in days {
-        let input = client
-            .get_input(year, day)
-            .unwrap_or_else(|_| panic!("failed to get input for {} day {}", year, day));
+    // Local inputs don't need an adventofcode.com session.
+    let client = if inputs.is_empty() {
+        Some(client(matches, config))
+    } else {
+        None
+    };
+    let client = client.as_ref();
 
-        let start = Instant::now();
-        let answers = solve(year, day, &input);
-        let time = Instant::now() - start;
+    if wait {
+        if days.len() != 1 {
+            eprintln!("error: --wait can only be used with a single day");
+            process::exit(2);
+        }
+        let (year, day) = days[0];
+        wait_for_unlock(client.expect("--wait conflicts with --input"), year, day);
+        if registry.get(year, day).is_none() {
+            println!("no solution registered for {} day {} yet", year, day);
+            return;
+        }
+    }
+
+    // Check the session up front rather than failing every download with an
+    // expired one.
+    if let (Some(client), false) = (client, matches.get_flag("offline")) {
+        let missing = days
+            .iter()
+            .filter(|&&(year, day)| !client.has_input(year, day))
+            .count();
+        if missing > 1 {
+            if let Err(err) = client.validate_session() {
+                eprintln!("error: {}", err);
+                process::exit(exit_code(&err));
+            }
+        }
+    }
+
+    // Fetching and solving can run in parallel, while printing, checking
+    // and submitting happen in day order on this thread.
+    let work = |&(year, day): &(i32, i32)| -> Result<Solved, (String, i32)> {
+        let inputs = match client {
+            Some(client) => vec![(
+                None,
+                client
+                    .get_input(year, day)
+                    .map_err(|err| (err.to_string(), exit_code(&err)))?,
+            )],
+            None => inputs.clone(),
+        };
+        Ok(inputs
+            .into_iter()
+            .map(|(source, input)| {
+                let run = match solution(&registry, year, day) {
+                    None => Some(Run::failed(year, day, "no solution registered".to_string())),
+                    Some(_) if bench => None,
+                    Some(parse) => Some(Run::solve(year, day, parse, &input, timeout)),
+                };
+                (source, input, run)
+            })
+            .collect())
+    };
+
+    let mut results = Results {
+        options: Options {
+            show_time,
+            submit,
+            budget,
+            bench,
+            json,
+            format,
+            verify: matches.get_flag("verify"),
+            record: matches.get_flag("record"),
+            all,
+        },
+        registry: &registry,
+        client,
+        recorded,
+        reports: Vec::new(),
+        runs: Vec::new(),
+        mismatches: Vec::new(),
+        verified: HashMap::new(),
+        fetch_failure: None,
+    };
+    runner::parallel(jobs, &days, work, |&(year, day), result| {
+        results.add(year, day, result)
+    });
+    results.finish();
+}
+
+/// The inputs of a day with their source when given with `--input`, each
+/// with its run unless benchmarking.
+type Solved = Vec<(Option<String>, String, Option<Run>)>;
+
+/// What `run_command` does with each solved day.
+struct Options {
+    show_time: bool,
+    submit: Option<u8>,
+    budget: Budget,
+    bench: bool,
+    json: bool,
+    format: Format,
+    verify: bool,
+    record: bool,
+    all: bool,
+}
+
+/// Prints, checks and submits solved days in day order, collecting what the
+/// summary and the exit code need.
+struct Results<'a> {
+    options: Options,
+    registry: &'a Registry,
+    client: Option<&'a Client>,
+    recorded: Answers,
+    reports: Vec<serde_json::Value>,
+    runs: Vec<Run>,
+    mismatches: Vec<(i32, i32, u8, Answer, Check)>,
+    verified: HashMap<(i32, i32, u8), Option<bool>>,
+    fetch_failure: Option<i32>,
+}
+
+impl<'a> Results<'a> {
+    fn client(&self) -> &'a Client {
+        self.client.expect("client is only missing with --input")
+    }
+
+    fn add(&mut self, year: i32, day: i32, result: Result<Solved, (String, i32)>) {
+        let solved = match result {
+            Ok(solved) => solved,
+            Err((err, code)) => {
+                self.fetch_failure = self.fetch_failure.or(Some(code));
+                // Machine readable formats have the failure in the records.
+                if (!self.options.all || self.options.bench) && self.options.format == Format::Text
+                {
+                    println!("Day {}", day);
+                    println!("  failed to get input: {}", err);
+                }
+                self.runs.push(Run::failed(year, day, err));
+                return;
+            }
+        };
+
+        for (source, input, run) in solved {
+            let run = match run {
+                Some(run) => run,
+                None => {
+                    self.bench(year, day, source, &input);
+                    continue;
+                }
+            };
+
+            if self.options.record {
+                record_accepted(self.client(), &mut self.recorded, year, day);
+            }
+
+            if self.options.all || self.options.format != Format::Text {
+                for (part, answer) in run.parts().into_iter().flatten() {
+                    if let (Ok(answer), true) = (answer, self.options.verify) {
+                        self.verify(year, day, part, answer);
+                    }
+                }
+                self.runs.push(run);
+                continue;
+            }
+            self.print(year, day, source, run);
+        }
+    }
+
+    fn bench(&mut self, year: i32, day: i32, source: Option<String>, input: &str) {
+        let parse = solution(self.registry, year, day).expect("checked when solving");
+        let budget = self.options.budget;
+        match runner::catch(|| bench::bench(year, day, parse, input, budget)) {
+            Ok(report) if self.options.json => self.reports.push(report.to_json()),
+            Ok(report) => println!("{}", report),
+            Err(panic) => {
+                let source = source.map(|source| format!(" [{}]", source));
+                if !self.options.json {
+                    println!("Day {}{}", day, source.unwrap_or_default());
+                    println!("  panicked: {}", panic);
+                }
+                let reason = format!("panicked: {}", panic);
+                self.runs.push(Run::failed(year, day, reason));
+            }
+        }
+    }
+
+    /// Checks an answer against the recorded one, remembering the result
+    /// for the records and the summary.
+    fn verify(&mut self, year: i32, day: i32, part: u8, answer: &Answer) -> Check {
+        let check = check_answer(&mut self.recorded, year, day, part, answer);
+        self.verified.insert((year, day, part), check.matched());
+        if let Check::Mismatch(_) = check {
+            self.mismatches
+                .push((year, day, part, answer.clone(), check.clone()));
+        }
+        check
+    }
 
-        if show_time {
+    /// Prints the answers of a day as text, then submits one if asked to.
+    fn print(&mut self, year: i32, day: i32, source: Option<String>, run: Run) {
+        let time = run.time();
+        let source = source.map(|source| format!(" [{}]", source));
+        if self.options.show_time {
             let time = time.as_secs() as f64 + f64::from(time.subsec_nanos()) * 1e-9;
-            println!("Day {} ({:.2}s)", day, time);
+            println!("Day {}{} ({:.2}s)", day, source.unwrap_or_default(), time);
         } else {
-            println!("Day {}", day);
+            println!("Day {}{}", day, source.unwrap_or_default());
         }
 
-        println!("  puzzle 1: {}", answers.0);
-        println!("  puzzle 2: {}", answers.1);
+        match &run.outcome {
+            Outcome::Unparsed(failure) => println!("  parsing {}", failure),
+            Outcome::Failed(reason) => println!("  {}", reason),
+            Outcome::Solved(..) => {}
+        }
+        for (part, answer) in run.parts().into_iter().flatten() {
+            let answer = match answer {
+                Ok(answer) => answer,
+                Err(failure) => {
+                    println!("  puzzle {}: {}", part, failure);
+                    continue;
+                }
+            };
+            if self.options.verify {
+                let check = self.verify(year, day, part, answer);
+                println!("  puzzle {}: {} ({})", part, answer, check.colored());
+            } else {
+                println!("  puzzle {}: {}", part, answer);
+            }
+        }
+
+        let answer = self.options.submit.and_then(|part| {
+            let (_, answer) = run.parts()?[usize::from(part) - 1];
+            Some((part, answer.as_ref().ok()?.submission()))
+        });
+        self.runs.push(run);
+        match answer {
+            Some((part, None)) => println!(
+                "  cannot submit puzzle {}: the drawing could not be read",
+                part
+            ),
+            Some((part, Some(answer))) => self.submit(year, day, part, &answer),
+            None => {}
+        }
     }
-}
 
-macro_rules! advent {
-    ($(
-        $year:tt {
-            $($day:tt,)*
-        },
-    )*) => {
-
-        fn solve(year: i32, day: i32, input: &str) -> (String, String) {
-            let year_str = format!("year{}", year);
-            let day_str = format!("day{:02}", day);
-
-            match (year_str.as_str(), day_str.as_str()) {
-                $(
-                    $(
-                        (stringify!($year), stringify!($day)) => (
-                            $year::$day::puzzle1(input).to_string(),
-                            $year::$day::puzzle2(input).to_string(),
-                        ),
-                    )*
-                )*
-                _ => panic!("failed to get input for year {} day {}", year, day),
+    fn submit(&mut self, year: i32, day: i32, part: u8, answer: &str) {
+        match self.client().submit_answer(year, day, part, answer) {
+            Ok(submission) => {
+                println!("  submitted puzzle {}: {}", part, submission);
+                if self.options.record && submission == Submission::Correct {
+                    if let Err(err) = self.recorded.record(year, day, part, answer, true) {
+                        answers_error(err);
+                    }
+                }
+            }
+            Err(err) => println!("  failed to submit puzzle {}: {}", part, err),
+        }
+    }
+
+    /// Prints the summary or records, saves recorded answers and exits
+    /// with a failure code if anything went wrong.
+    fn finish(mut self) {
+        let options = &self.options;
+        let mut failed = false;
+        if options.format != Format::Text {
+            failed = self.runs.iter().any(|run| !run.failures().is_empty());
+            let mut records = self
+                .runs
+                .iter()
+                .flat_map(format::records)
+                .collect::<Vec<_>>();
+            for record in &mut records {
+                record.verified = self
+                    .verified
+                    .get(&(record.year, record.day, record.part))
+                    .copied()
+                    .flatten();
             }
+            match options.format {
+                Format::Json => println!("{}", format::to_json(&records)),
+                Format::Csv => print!("{}", format::to_csv(&records)),
+                Format::Text => unreachable!(),
+            }
+        } else if options.all && !options.bench {
+            failed = !self.runs.iter().all(|run| run.failures().is_empty());
+            runner::print_summary(&self.runs);
+            if !self.mismatches.is_empty() {
+                println!();
+                for (year, day, part, answer, check) in &self.mismatches {
+                    println!(
+                        "  {} day {} puzzle {}: {} ({})",
+                        year,
+                        day,
+                        part,
+                        answer,
+                        check.colored()
+                    );
+                }
+            }
+        } else if options.json {
+            // Keep the JSON parseable; failures go to stderr instead.
+            for run in &self.runs {
+                for failure in run.failures() {
+                    eprintln!("{}", failure);
+                    failed = true;
+                }
+            }
+        } else if self.runs.iter().any(|run| !run.failures().is_empty()) {
+            println!();
+            println!("Failures:");
+            failed = runner::print_failures(&self.runs);
+        }
+
+        if let Err(err) = self.recorded.save() {
+            answers_error(err);
+        }
+
+        if self.options.json {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&self.reports).expect("failed to serialize reports")
+            );
+        }
+
+        if let Some(code) = self.fetch_failure {
+            process::exit(code);
+        }
+        if failed || !self.mismatches.is_empty() {
+            process::exit(1);
+        }
+    }
+}
+
+/// Checks an answer against the recorded one.
+fn check_answer(recorded: &mut Answers, year: i32, day: i32, part: u8, answer: &Answer) -> Check {
+    let expected = recorded
+        .expected(year, day, part)
+        .unwrap_or_else(|err| answers_error(err));
+    Check::new(expected.as_deref(), answer)
+}
+
+/// Records the answers the site accepted for a day, as listed on its puzzle
+/// page, so that only answers known to be right end up in the file.
+fn record_accepted(client: &Client, recorded: &mut Answers, year: i32, day: i32) {
+    let puzzle = match client.get_puzzle(year, day) {
+        Ok(puzzle) => puzzle,
+        Err(err) => {
+            eprintln!(
+                "warning: cannot record answers for {} day {}: {}",
+                year, day, err
+            );
+            return;
         }
     };
+    for (part, answer) in (1..=2).zip(puzzle.accepted_answers()) {
+        if let Err(err) = recorded.record(year, day, part, &answer, false) {
+            answers_error(err);
+        }
+    }
+}
+
+fn answers_error(err: Error) -> ! {
+    eprintln!("error: {}", err);
+    process::exit(2);
+}
+
+/// Counts down to the unlock of a puzzle, then fetches its input, retrying
+/// for a while since the server may lag behind the release.
+fn wait_for_unlock(client: &Client, year: i32, day: i32) {
+    const RETRIES: usize = 10;
+
+    if let Err(err @ CalendarError::NoSuchDay { .. }) =
+        calendar::check_unlocked(year, day, Utc::now())
+    {
+        eprintln!("error: {}", err);
+        process::exit(exit_code(&err.into()));
+    }
+    let unlock = calendar::unlock_time(year, day);
+    loop {
+        let remaining = unlock - Utc::now();
+        if remaining <= chrono::Duration::zero() {
+            break;
+        }
+        eprint!(
+            "\r{} day {} unlocks in {}   ",
+            year,
+            day,
+            calendar::format_duration(remaining)
+        );
+        io::stderr().flush().expect("failed to flush stderr");
+        let remaining = remaining.to_std().expect("remaining time is positive");
+        thread::sleep(remaining.min(Duration::from_secs(1)));
+    }
+    eprintln!();
+
+    for attempt in 1..=RETRIES {
+        match client.get_input(year, day) {
+            Ok(_) => return,
+            Err(err) if attempt < RETRIES => {
+                eprintln!("failed to get input, retrying: {}", err);
+                thread::sleep(Duration::from_secs(1));
+            }
+            Err(err) => fail(&format!("input for {} day {}", year, day), err),
+        }
+    }
 }
 
-advent!(
-    year2023 {
-        day01,
-        day02,
-        day03,
-        day04,
-        day05,
-        day06,
-        day07,
-        day08,
-        day09,
-        day10,
-        day11,
-        day12,
-        day13,
-        day14,
-        day15,
-        day16,
-        // day17,
-        // day18,
-        // day19,
-    },
-    year2022 {
-        day01,
-        day02,
-        day03,
-        day04,
-        day05,
-        day06,
-        day07,
-        day08,
-        day09,
-        day10,
-    },
-    year2020 {
-        day01,
-        day02,
-        day03,
-        // day04,
-    },
-    year2019 {
-        day01,
-        day02,
-        day03,
-        day04,
-        day05,
-    },
-    // year2018 {
-    //     day01,
-    //     //        day02,
-    // },
-    year2016 {
-        day01,
-        day02,
-        day03,
-        day04,
-        day05,
-        day06,
-        day07,
-        day08,
-        day09,
-        day10,
-        // day11,
-        // day12,
-        // day13,
-        // day14,
-        // day15,
-        // day16,
-        // day17,
-        // day18,
-        // day19,
-        //        day20,
-        //        day22,
-        //        day23,
-        //        day24,
-        //        day25,
-    },
-    year2015 {
-        day01,
-        day02,
-        day03,
-        day04,
-        day09,
-        day10,
-        day11,
-        day12,
-        day13,
-        day14,
-        day15,
-        day16,
-        day17,
-        day18,
-        day19,
-        //        day20,
-        //        day22,
-        //        day23,
-        //        day24,
-        //        day25,
-    },
-);
+/// Exit codes for failing to fetch from adventofcode.com, next to 1 for
+/// failed or wrong solutions and 2 for invalid arguments.
+const EXIT_NOT_UNLOCKED: i32 = 3;
+const EXIT_SESSION: i32 = 4;
+const EXIT_NOT_FOUND: i32 = 5;
+const EXIT_NETWORK: i32 = 6;
+const EXIT_TLS: i32 = 7;
+
+fn exit_code(err: &Error) -> i32 {
+    if let Some(err) = err.downcast_ref::<ClientError>() {
+        return match err {
+            ClientError::NotUnlocked(_) => EXIT_NOT_UNLOCKED,
+            ClientError::NoSession(_) | ClientError::InvalidSession => EXIT_SESSION,
+            ClientError::NotFound(_) => EXIT_NOT_FOUND,
+            ClientError::Unreachable(..) => EXIT_NETWORK,
+            ClientError::Tls(..) => EXIT_TLS,
+            ClientError::Offline | ClientError::UnrecognizedSubmission => 1,
+        };
+    }
+    match err.downcast_ref::<CalendarError>() {
+        Some(CalendarError::Locked { .. }) => EXIT_NOT_UNLOCKED,
+        Some(CalendarError::NoSuchDay { .. }) => EXIT_NOT_FOUND,
+        Some(_) => 2,
+        None => 1,
+    }
+}
+
+/// Reports a failure to fetch something and exits with a code telling why.
+fn fail(what: &str, err: Error) -> ! {
+    eprintln!("error: failed to get {}: {}", what, err);
+    process::exit(exit_code(&err));
+}
+
+/// The `--year` argument, defaulting to the configured year and then to the
+/// running or last event.
+fn year(matches: &ArgMatches, config: &Config) -> i32 {
+    matches
+        .get_one::<i32>("year")
+        .copied()
+        .or(config.year)
+        .unwrap_or_else(|| calendar::default_year(Utc::now()))
+}
+
+/// A day argument, defaulting to the latest unlocked day.
+fn day(matches: &ArgMatches, year: i32, id: &str) -> i32 {
+    let day = matches
+        .get_one::<String>(id)
+        .map_or("today", String::as_str);
+    parse_day(year, day)
+}
+
+/// Parses a positive number of seconds, which may be fractional.
+fn parse_seconds(value: &str) -> Result<Duration, String> {
+    let secs = value
+        .parse::<f64>()
+        .map_err(|_| format!("{} is not a number", value))?;
+    if !secs.is_finite() || secs <= 0.0 {
+        return Err(format!("{} is not a positive number of seconds", value));
+    }
+    Duration::try_from_secs_f64(secs).map_err(|err| err.to_string())
+}
+
+fn parse_day(year: i32, day: &str) -> i32 {
+    calendar::parse_day(year, day, Utc::now()).unwrap_or_else(|err| {
+        eprintln!("error: {}", err);
+        process::exit(exit_code(&err.into()));
+    })
+}
+
+fn fetch_sample(client: &Client, year: i32, day: i32, block: Option<usize>) -> String {
+    let puzzle = client
+        .get_puzzle(year, day)
+        .unwrap_or_else(|err| fail(&format!("puzzle for {} day {}", year, day), err));
+    let blocks = puzzle.code_blocks();
+    if blocks.is_empty() {
+        eprintln!("error: no code blocks in {} day {}", year, day);
+        process::exit(2);
+    }
+    let block = block.unwrap_or_else(|| choose_block(&blocks));
+    match blocks.get(block.wrapping_sub(1)) {
+        Some(sample) => sample.clone(),
+        None => {
+            eprintln!("error: no code block {} in {} day {}", block, year, day);
+            process::exit(2);
+        }
+    }
+}
+
+/// Lists the code blocks of a puzzle and reads the number of the one to use
+/// from stdin.
+fn choose_block(blocks: &[String]) -> usize {
+    for (i, block) in blocks.iter().enumerate() {
+        println!("[{}]", i + 1);
+        for line in block.lines().take(5) {
+            println!("    {}", line);
+        }
+        if block.lines().count() > 5 {
+            println!("    ...");
+        }
+    }
+    print!("block: ");
+    io::stdout().flush().expect("failed to flush stdout");
+
+    let mut line = String::new();
+    io::stdin()
+        .read_line(&mut line)
+        .expect("failed to read block number");
+    line.trim().parse().unwrap_or_else(|_| {
+        eprintln!("error: invalid block number {:?}", line.trim());
+        process::exit(2);
+    })
+}
+
+fn write_fixture(path: &Path, sample: &str) {
+    if let Err(err) = fixtures::write_fixture(path, sample) {
+        eprintln!("error: failed to write fixture: {}", err);
+        process::exit(2);
+    }
+}
+
+fn cache(matches: &ArgMatches, config: &Config) -> Cache {
+    match matches.get_one::<PathBuf>("cache-dir") {
+        Some(dir) => Cache::new(dir),
+        None => Cache::from_env(config.cache_dir.as_deref()),
+    }
+}
+
+fn client(matches: &ArgMatches, config: &Config) -> Client {
+    let cache = cache(matches, config);
+    if matches.get_flag("offline") {
+        Client::offline(cache)
+    } else {
+        let client = Client::from_config(config).unwrap_or_else(|err| {
+            eprintln!("error: {}", err);
+            process::exit(2);
+        });
+        client.with_cache(cache)
+    }
+}
+
+pub fn registry() -> Registry {
+    let mut registry = Registry::new();
+    year2015::register(&mut registry);
+    year2016::register(&mut registry);
+    year2019::register(&mut registry);
+    year2020::register(&mut registry);
+    year2022::register(&mut registry);
+    year2023::register(&mut registry);
+    registry
+}
+
+fn solution(registry: &Registry, year: i32, day: i32) -> Option<Parser> {
+    registry.get(year, day)
+}
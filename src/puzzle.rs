@@ -0,0 +1,403 @@
+use colored::Colorize;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+const WIDTH: usize = 80;
+
+lazy_static! {
+    static ref ARTICLE_RE: Regex =
+        Regex::new(r#"(?s)<article class="day-desc">(.*?)</article>"#).unwrap();
+    static ref CODE_BLOCK_RE: Regex = Regex::new(r"(?s)<pre><code>(.*?)</code></pre>").unwrap();
+    static ref TAG_RE: Regex = Regex::new(r"<[^>]*>").unwrap();
+    static ref ANSWER_RE: Regex = Regex::new(r"Your puzzle answer was <code>(.*?)</code>").unwrap();
+    static ref ENTITY_RE: Regex = Regex::new(r"&(#x?[0-9a-fA-F]+|[a-z]+);").unwrap();
+}
+
+/// A puzzle description page as served by `/{year}/day/{day}`.
+#[derive(Clone, Debug)]
+pub struct Puzzle {
+    html: String,
+}
+
+impl Puzzle {
+    pub fn new<S: Into<String>>(html: S) -> Puzzle {
+        Puzzle { html: html.into() }
+    }
+
+    /// The inner HTML of the `day-desc` articles; the second one only
+    /// appears once part 1 is solved.
+    pub fn articles(&self) -> Vec<&str> {
+        ARTICLE_RE
+            .captures_iter(&self.html)
+            .map(|caps| caps.get(1).unwrap().as_str())
+            .collect()
+    }
+
+    /// The answers the site accepted, which it shows below each solved part.
+    pub fn accepted_answers(&self) -> Vec<String> {
+        ANSWER_RE
+            .captures_iter(&self.html)
+            .map(|caps| decode_entities(&caps[1]))
+            .collect()
+    }
+
+    /// The text of all `<pre><code>` blocks in the description, which is
+    /// where the examples live.
+    pub fn code_blocks(&self) -> Vec<String> {
+        self.articles()
+            .into_iter()
+            .flat_map(|article| CODE_BLOCK_RE.captures_iter(article))
+            .map(|caps| decode_entities(&TAG_RE.replace_all(&caps[1], "")))
+            .collect()
+    }
+
+    /// Renders the puzzle description as terminal text, highlighting emphasis
+    /// and code with ANSI colors when `color` is set.
+    pub fn render(&self, color: bool) -> String {
+        let blocks = self
+            .articles()
+            .into_iter()
+            .flat_map(parse_blocks)
+            .collect::<Vec<_>>();
+        blocks
+            .iter()
+            .map(|block| block.render(color))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Style {
+    em: bool,
+    code: bool,
+}
+
+#[derive(Debug, PartialEq)]
+struct Span {
+    text: String,
+    style: Style,
+}
+
+#[derive(Debug, PartialEq)]
+enum Kind {
+    Heading,
+    Paragraph,
+    Item,
+}
+
+#[derive(Debug, PartialEq)]
+enum Block {
+    Text(Kind, Vec<Span>),
+    Code(String),
+}
+
+impl Block {
+    fn render(&self, color: bool) -> String {
+        match self {
+            Block::Text(kind, spans) => {
+                let (first, rest) = match kind {
+                    Kind::Item => ("  - ", "    "),
+                    _ => ("", ""),
+                };
+                let style = |span: &Span, word: &str| -> String {
+                    if !color {
+                        return word.to_string();
+                    }
+                    match (kind, span.style.em, span.style.code) {
+                        (Kind::Heading, ..) => word.bold().to_string(),
+                        (_, true, true) => word.bold().cyan().to_string(),
+                        (_, true, false) => word.bold().bright_white().to_string(),
+                        (_, false, true) => word.cyan().to_string(),
+                        _ => word.to_string(),
+                    }
+                };
+                wrap(spans, first, rest, style)
+            }
+            Block::Code(code) => code
+                .trim_end_matches('\n')
+                .lines()
+                .map(|line| {
+                    let line = format!("    {}", line);
+                    if color {
+                        line.cyan().to_string()
+                    } else {
+                        line
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+/// Word-wraps spans to `WIDTH` columns. Words are split on whitespace, but
+/// adjacent spans without whitespace between them stay glued together.
+fn wrap<F>(spans: &[Span], first: &str, rest: &str, style: F) -> String
+where
+    F: Fn(&Span, &str) -> String,
+{
+    let mut out = first.to_string();
+    let mut column = first.len();
+    let mut pending_space = false;
+    for span in spans {
+        let mut leading = true;
+        for word in span.text.split(' ') {
+            if !leading {
+                pending_space = true;
+            }
+            leading = false;
+            if word.is_empty() {
+                continue;
+            }
+
+            let len = word.chars().count();
+            if pending_space {
+                if column + 1 + len > WIDTH && column > rest.len() {
+                    out.push('\n');
+                    out.push_str(rest);
+                    column = rest.len();
+                } else {
+                    out.push(' ');
+                    column += 1;
+                }
+                pending_space = false;
+            }
+            out.push_str(&style(span, word));
+            column += len;
+        }
+    }
+    out
+}
+
+pub fn decode_entities(text: &str) -> String {
+    ENTITY_RE
+        .replace_all(text, |caps: &regex::Captures| {
+            let entity = &caps[1];
+            let decoded = match entity {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ if entity.starts_with("#x") => u32::from_str_radix(&entity[2..], 16)
+                    .ok()
+                    .and_then(std::char::from_u32),
+                _ if entity.starts_with('#') => {
+                    entity[1..].parse().ok().and_then(std::char::from_u32)
+                }
+                _ => None,
+            };
+            decoded.map_or_else(|| caps[0].to_string(), |c| c.to_string())
+        })
+        .into_owned()
+}
+
+enum Token<'a> {
+    Text(&'a str),
+    Tag(&'a str),
+}
+
+fn tokens(html: &str) -> impl '_ + Iterator<Item = Token<'_>> {
+    let mut rest = html;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let (token, len) = match rest.find('<') {
+            Some(0) => {
+                let end = rest.find('>').map_or(rest.len(), |i| i + 1);
+                (Token::Tag(&rest[..end]), end)
+            }
+            Some(i) => (Token::Text(&rest[..i]), i),
+            None => (Token::Text(rest), rest.len()),
+        };
+        rest = &rest[len..];
+        Some(token)
+    })
+}
+
+fn parse_blocks(html: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(Kind, Vec<Span>)> = None;
+    let mut code: Option<String> = None;
+    let mut style = Style::default();
+
+    for token in tokens(html) {
+        let tag = match token {
+            Token::Tag(tag) => tag,
+            Token::Text(text) => {
+                let text = decode_entities(text);
+                if let Some(code) = &mut code {
+                    code.push_str(&text);
+                    continue;
+                }
+
+                // Collapse whitespace, keeping a single space at either end so
+                // that words in adjacent spans stay separated.
+                let words = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                if words.is_empty() && current.is_none() {
+                    continue;
+                }
+                let mut collapsed = String::new();
+                if text.starts_with(char::is_whitespace) {
+                    collapsed.push(' ');
+                }
+                collapsed.push_str(&words);
+                if !words.is_empty() && text.ends_with(char::is_whitespace) {
+                    collapsed.push(' ');
+                }
+                current
+                    .get_or_insert_with(|| (Kind::Paragraph, Vec::new()))
+                    .1
+                    .push(Span {
+                        text: collapsed,
+                        style,
+                    });
+                continue;
+            }
+        };
+        let closing = tag.starts_with("</");
+        let name = tag
+            .trim_start_matches("</")
+            .trim_start_matches('<')
+            .trim_end_matches('>')
+            .trim_end_matches('/')
+            .split_whitespace()
+            .next()
+            .unwrap_or("");
+
+        let kind = match name {
+            "h2" => Some(Kind::Heading),
+            "p" => Some(Kind::Paragraph),
+            "li" => Some(Kind::Item),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            flush(&mut blocks, &mut current);
+            if !closing {
+                current = Some((kind, Vec::new()));
+            }
+            continue;
+        }
+
+        match (name, closing) {
+            ("pre", false) => {
+                flush(&mut blocks, &mut current);
+                code = Some(String::new());
+            }
+            ("pre", true) => {
+                if let Some(code) = code.take() {
+                    blocks.push(Block::Code(code));
+                }
+            }
+            ("em", _) => style.em = !closing,
+            ("code", _) if code.is_none() => style.code = !closing,
+            ("br", _) => flush(&mut blocks, &mut current),
+            _ => {}
+        }
+    }
+    flush(&mut blocks, &mut current);
+    blocks
+}
+
+fn flush(blocks: &mut Vec<Block>, current: &mut Option<(Kind, Vec<Span>)>) {
+    if let Some((kind, spans)) = current.take() {
+        if spans.iter().any(|span| !span.text.trim().is_empty()) {
+            blocks.push(Block::Text(kind, spans));
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    pub const PAGE: &str = r#"<!DOCTYPE html>
+<html lang="en-us">
+<body>
+<main>
+<article class="day-desc"><h2>--- Day 1: Trebuchet?! ---</h2><p>Something is wrong with global snow production, and you've been selected to take a look.</p>
+<p>For example:</p>
+<pre><code>1abc2
+pqr3stu8vwx
+a1b2c3d4e5f
+</code></pre>
+<p>In this example, the calibration values of these four lines are <code>12</code>, <code>38</code>, and <code><em>15</em></code>. Adding these together produces <code><em>142</em></code>.</p>
+<ul>
+<li>Values &lt; 3 &amp; <em>more</em>.</li>
+</ul>
+</article>
+<p>Your puzzle answer was <code>54634</code>.</p>
+<article class="day-desc"><h2 id="part2">--- Part Two ---</h2><p>Your calculation isn't quite right.</p>
+<pre><code>two1nine
+eightwothree
+</code></pre>
+</article>
+</main>
+</body>
+</html>
+"#;
+
+    #[test]
+    fn articles() {
+        let puzzle = Puzzle::new(PAGE);
+        let articles = puzzle.articles();
+        assert_eq!(articles.len(), 2);
+        assert!(articles[1].starts_with("<h2 id=\"part2\">--- Part Two ---</h2>"));
+    }
+
+    #[test]
+    fn render_plain() {
+        assert_eq!(
+            Puzzle::new(PAGE).render(false),
+            "--- Day 1: Trebuchet?! ---
+
+Something is wrong with global snow production, and you've been selected to take
+a look.
+
+For example:
+
+    1abc2
+    pqr3stu8vwx
+    a1b2c3d4e5f
+
+In this example, the calibration values of these four lines are 12, 38, and 15.
+Adding these together produces 142.
+
+  - Values < 3 & more.
+
+--- Part Two ---
+
+Your calculation isn't quite right.
+
+    two1nine
+    eightwothree"
+        );
+    }
+
+    #[test]
+    fn code_blocks() {
+        assert_eq!(
+            Puzzle::new(PAGE).code_blocks(),
+            vec![
+                "1abc2\npqr3stu8vwx\na1b2c3d4e5f\n",
+                "two1nine\neightwothree\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn accepted_answers() {
+        assert_eq!(Puzzle::new(PAGE).accepted_answers(), vec!["54634"]);
+    }
+
+    #[test]
+    fn entities() {
+        assert_eq!(
+            decode_entities("&lt;a&gt; &amp;&#39;&#x41;&bogus;"),
+            "<a> &'A&bogus;"
+        );
+    }
+}
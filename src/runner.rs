@@ -0,0 +1,525 @@
+use crate::answer::Answer;
+use crate::solution::Parser;
+use cpu_time::ThreadTime;
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Once;
+use std::thread;
+use std::time::Duration;
+
+const ANSWER_WIDTH: usize = 20;
+
+/// A panic caught while running a solution.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Panic {
+    pub message: String,
+    pub location: Option<String>,
+}
+
+impl Display for Panic {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "{} at {}", self.message, location),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+thread_local! {
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+    static LAST_PANIC: RefCell<Option<Panic>> = const { RefCell::new(None) };
+}
+
+/// Runs `f`, turning a panic into an error carrying its message and source
+/// location. The panic is not printed; callers are expected to report it.
+pub fn catch<T, F: FnOnce() -> T>(f: F) -> Result<T, Panic> {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let default = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !CATCHING.with(Cell::get) {
+                return default(info);
+            }
+            let panic = Panic {
+                message: panic_message(info.payload()),
+                location: info.location().map(ToString::to_string),
+            };
+            LAST_PANIC.with(|last| *last.borrow_mut() = Some(panic));
+        }));
+    });
+
+    let catching = CATCHING.with(|catching| catching.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING.with(|c| c.set(catching));
+
+    result.map_err(|payload| {
+        LAST_PANIC
+            .with(|last| last.borrow_mut().take())
+            .unwrap_or_else(|| Panic {
+                message: panic_message(&*payload),
+                location: None,
+            })
+    })
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "<unknown panic payload>".to_string()
+    }
+}
+
+/// Why a phase of a solution did not produce a result.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Failure {
+    Panicked(Panic),
+    TimedOut(Duration),
+    /// Not run because an earlier phase timed out.
+    Skipped,
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Failure::Panicked(panic) => write!(f, "panicked: {}", panic),
+            Failure::TimedOut(timeout) => write!(f, "timed out after {:.2?}", timeout),
+            Failure::Skipped => write!(f, "skipped after timeout"),
+        }
+    }
+}
+
+pub type Part = Result<Answer, Failure>;
+
+#[derive(Clone, Debug)]
+pub enum Outcome {
+    Solved(Part, Part),
+    /// The input could not be parsed, so neither part ran.
+    Unparsed(Failure),
+    /// The input could not be obtained.
+    Failed(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct Run {
+    pub year: i32,
+    pub day: i32,
+    pub outcome: Outcome,
+    /// CPU time spent parsing and in each part, which stays meaningful when
+    /// days run in parallel. Phases that timed out count for the full
+    /// timeout.
+    pub times: [Duration; 3],
+}
+
+impl Run {
+    /// Solves both puzzles, catching panics in parsing and in each part
+    /// separately. With a `timeout`, the solution runs on a worker thread
+    /// and each phase is abandoned once it exceeds the timeout.
+    pub fn solve(
+        year: i32,
+        day: i32,
+        parse: Parser,
+        input: &str,
+        timeout: Option<Duration>,
+    ) -> Run {
+        let (outcome, times) = match timeout {
+            Some(timeout) => solve_with_timeout(year, day, parse, input, timeout),
+            None => match timed(|| catch(|| parse(input))) {
+                (Ok(parsed), parse_time) => {
+                    let (part1, part1_time) = timed(|| catch(|| parsed.part1()));
+                    let (part2, part2_time) = timed(|| catch(|| parsed.part2()));
+                    (
+                        Outcome::Solved(
+                            part1.map_err(Failure::Panicked),
+                            part2.map_err(Failure::Panicked),
+                        ),
+                        [parse_time, part1_time, part2_time],
+                    )
+                }
+                (Err(panic), parse_time) => (
+                    Outcome::Unparsed(Failure::Panicked(panic)),
+                    [parse_time, Duration::default(), Duration::default()],
+                ),
+            },
+        };
+
+        Run {
+            year,
+            day,
+            outcome,
+            times,
+        }
+    }
+
+    pub fn failed(year: i32, day: i32, reason: String) -> Run {
+        Run {
+            year,
+            day,
+            outcome: Outcome::Failed(reason),
+            times: Default::default(),
+        }
+    }
+
+    pub fn time(&self) -> Duration {
+        self.times.iter().sum()
+    }
+
+    /// Returns the answer or panic for each part, if the parts ran at all.
+    pub fn parts(&self) -> Option<[(u8, &Part); 2]> {
+        match &self.outcome {
+            Outcome::Solved(part1, part2) => Some([(1, part1), (2, part2)]),
+            _ => None,
+        }
+    }
+
+    /// Describes everything that went wrong in this run.
+    pub fn failures(&self) -> Vec<String> {
+        let prefix = format!("{} day {}", self.year, self.day);
+        match &self.outcome {
+            Outcome::Solved(..) => self
+                .parts()
+                .into_iter()
+                .flatten()
+                .filter_map(|(part, result)| {
+                    let failure = result.as_ref().err()?;
+                    Some(format!("{} puzzle {} {}", prefix, part, failure))
+                })
+                .collect(),
+            Outcome::Unparsed(failure) => {
+                vec![format!("{} parsing {}", prefix, failure)]
+            }
+            Outcome::Failed(reason) => vec![format!("{} failed: {}", prefix, reason)],
+        }
+    }
+
+    /// Whether either answer looks like an unimplemented stub returning 0.
+    pub fn is_stubbed(&self) -> bool {
+        self.parts()
+            .into_iter()
+            .flatten()
+            .any(|(_, result)| matches!(result, Ok(answer) if answer.is_stub()))
+    }
+}
+
+/// Runs a solution on a detached worker thread that reports each phase and
+/// its CPU time over a channel. Rust threads cannot be killed, so
+/// a timed out worker keeps running in the background until the process
+/// exits.
+fn solve_with_timeout(
+    year: i32,
+    day: i32,
+    parse: Parser,
+    input: &str,
+    timeout: Duration,
+) -> (Outcome, [Duration; 3]) {
+    let (tx, rx) = mpsc::channel();
+    let input = input.to_owned();
+    thread::Builder::new()
+        .name(format!("{} day {}", year, day))
+        .spawn(move || {
+            let parsed = match timed(|| catch(|| parse(&input))) {
+                (Ok(parsed), time) => {
+                    let _ = tx.send((Ok(None), time));
+                    parsed
+                }
+                (Err(panic), time) => {
+                    let _ = tx.send((Err(panic), time));
+                    return;
+                }
+            };
+            let (part1, time) = timed(|| catch(|| parsed.part1()));
+            let _ = tx.send((part1.map(Some), time));
+            let (part2, time) = timed(|| catch(|| parsed.part2()));
+            let _ = tx.send((part2.map(Some), time));
+        })
+        .expect("failed to spawn solution thread");
+
+    let mut times = [Duration::default(); 3];
+    let mut recv = |phase: usize| match rx.recv_timeout(timeout) {
+        Ok((result, time)) => {
+            times[phase] = time;
+            result.map_err(Failure::Panicked)
+        }
+        Err(RecvTimeoutError::Timeout) => {
+            times[phase] = timeout;
+            Err(Failure::TimedOut(timeout))
+        }
+        Err(RecvTimeoutError::Disconnected) => unreachable!("solution thread exited early"),
+    };
+    let answer = |result: Result<Option<Answer>, Failure>| {
+        result.map(|answer| answer.expect("expected an answer"))
+    };
+
+    let outcome = match recv(0) {
+        Err(failure) => Outcome::Unparsed(failure),
+        Ok(_) => {
+            let part1 = answer(recv(1));
+            let part2 = match part1 {
+                Err(Failure::TimedOut(_)) => Err(Failure::Skipped),
+                _ => answer(recv(2)),
+            };
+            Outcome::Solved(part1, part2)
+        }
+    };
+    (outcome, times)
+}
+
+/// Runs `f`, measuring the CPU time of the current thread.
+fn timed<T, F: FnOnce() -> T>(f: F) -> (T, Duration) {
+    let start = ThreadTime::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+/// Runs `work` for every task on `jobs` worker threads, passing results to
+/// `done` on the calling thread in task order as soon as all earlier tasks
+/// have finished. A single job runs everything on the calling thread.
+pub fn parallel<T, R, W, D>(jobs: usize, tasks: &[T], work: W, mut done: D)
+where
+    T: Sync,
+    R: Send,
+    W: Fn(&T) -> R + Sync,
+    D: FnMut(&T, R),
+{
+    if jobs <= 1 {
+        for task in tasks {
+            done(task, work(task));
+        }
+        return;
+    }
+
+    let next = AtomicUsize::new(0);
+    let (tx, rx) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(tasks.len()) {
+            let (tx, next, work) = (tx.clone(), &next, &work);
+            scope.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let task = match tasks.get(i) {
+                    Some(task) => task,
+                    None => break,
+                };
+                if tx.send((i, work(task))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+
+        let mut pending = BTreeMap::new();
+        let mut ready = 0;
+        for (i, result) in rx {
+            pending.insert(i, result);
+            while let Some(result) = pending.remove(&ready) {
+                done(&tasks[ready], result);
+                ready += 1;
+            }
+        }
+    });
+}
+
+fn cell(answer: &str) -> String {
+    let answer = answer.trim();
+    if answer.contains('\n') {
+        return "<multi-line>".to_string();
+    }
+    if answer.chars().count() > ANSWER_WIDTH {
+        let truncated = answer.chars().take(ANSWER_WIDTH - 1).collect::<String>();
+        return format!("{}…", truncated);
+    }
+    answer.to_string()
+}
+
+fn part_cell(part: &Part) -> String {
+    match part {
+        Ok(answer) => cell(&answer.to_string()),
+        Err(Failure::Panicked(_)) => "<panicked>".to_string(),
+        Err(Failure::TimedOut(_)) => "<timed out>".to_string(),
+        Err(Failure::Skipped) => "<skipped>".to_string(),
+    }
+}
+
+/// Prints a compact table of answers and timings, followed by totals and
+/// the days that did not produce answers.
+pub fn print_summary(runs: &[Run]) {
+    let w = ANSWER_WIDTH;
+    println!(
+        "{:>4}  {:>3}  {:<w$}  {:<w$}  {:>8}",
+        "Year",
+        "Day",
+        "Puzzle 1",
+        "Puzzle 2",
+        "Time",
+        w = w
+    );
+    for run in runs {
+        let (part1, part2) = match &run.outcome {
+            Outcome::Solved(part1, part2) => (part_cell(part1), part_cell(part2)),
+            Outcome::Unparsed(failure) => (part_cell(&Err(failure.clone())), String::new()),
+            Outcome::Failed(_) => ("<failed>".to_string(), String::new()),
+        };
+        println!(
+            "{:>4}  {:>3}  {:<w$}  {:<w$}  {:>7.2}s",
+            run.year,
+            run.day,
+            part1,
+            part2,
+            run.time().as_secs_f64(),
+            w = w
+        );
+    }
+
+    let total = runs.iter().map(Run::time).sum::<Duration>();
+    let failed = runs.iter().filter(|run| !run.failures().is_empty()).count();
+    let stubbed = runs
+        .iter()
+        .filter(|run| run.failures().is_empty() && run.is_stubbed())
+        .collect::<Vec<_>>();
+
+    println!();
+    println!(
+        "{} days, {} solved, {} failed, {} unimplemented in {:.2}s",
+        runs.len(),
+        runs.len() - failed - stubbed.len(),
+        failed,
+        stubbed.len(),
+        total.as_secs_f64()
+    );
+    print_failures(runs);
+    for run in stubbed {
+        println!("  {} day {} is unimplemented", run.year, run.day);
+    }
+}
+
+/// Prints every failure across runs, returning whether there were any.
+pub fn print_failures(runs: &[Run]) -> bool {
+    let failures = runs.iter().flat_map(Run::failures).collect::<Vec<_>>();
+    for failure in &failures {
+        println!("  {}", failure);
+    }
+    !failures.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{Parsed, Puzzles};
+
+    fn puzzles(input: &str) -> Box<dyn Parsed> {
+        if input == "unparsable" {
+            panic!("cannot parse");
+        }
+        Box::new(Puzzles {
+            input: input.to_owned(),
+            puzzle1: |input| input.len().to_string(),
+            puzzle2: |input| match input {
+                "panic" => panic!("bad input {}", input),
+                "slow" => {
+                    thread::sleep(Duration::from_secs(1));
+                    "1".to_string()
+                }
+                _ => "0".to_string(),
+            },
+        })
+    }
+
+    #[test]
+    fn solve_catches_panics_per_part() {
+        let run = Run::solve(2023, 1, puzzles, "panic", None);
+        let parts = run.parts().unwrap();
+        assert_eq!(parts[0].1, &Ok(Answer::Integer(5)));
+
+        let panic = match parts[1].1 {
+            Err(Failure::Panicked(panic)) => panic,
+            result => panic!("unexpected result {:?}", result),
+        };
+        assert_eq!(panic.message, "bad input panic");
+        assert!(panic
+            .location
+            .as_ref()
+            .unwrap()
+            .starts_with("src/runner.rs:"));
+
+        let failures = run.failures();
+        assert_eq!(failures.len(), 1);
+        assert!(failures[0].starts_with("2023 day 1 puzzle 2 panicked: bad input panic at "));
+        assert!(!run.is_stubbed());
+    }
+
+    #[test]
+    fn solve_catches_parse_panics() {
+        let run = Run::solve(2023, 2, puzzles, "unparsable", None);
+        assert!(run.parts().is_none());
+        assert!(run.failures()[0].starts_with("2023 day 2 parsing panicked: cannot parse"));
+    }
+
+    #[test]
+    fn solve_detects_stubs() {
+        let run = Run::solve(2023, 1, puzzles, "input", None);
+        assert!(run.failures().is_empty());
+        assert!(run.is_stubbed());
+    }
+
+    #[test]
+    fn solve_with_timeouts() {
+        let timeout = Some(Duration::from_millis(50));
+        let run = Run::solve(2023, 3, puzzles, "slow", timeout);
+        let parts = run.parts().unwrap();
+        assert_eq!(parts[0].1, &Ok(Answer::Integer(4)));
+        assert_eq!(
+            parts[1].1,
+            &Err(Failure::TimedOut(Duration::from_millis(50)))
+        );
+        assert_eq!(run.times[2], Duration::from_millis(50));
+
+        let run = Run::solve(2023, 3, puzzles, "panic", timeout);
+        assert!(run.failures()[0].contains("puzzle 2 panicked: bad input panic at "));
+
+        let run = Run::solve(2023, 3, puzzles, "unparsable", timeout);
+        assert!(run.failures()[0].starts_with("2023 day 3 parsing panicked: cannot parse"));
+    }
+
+    #[test]
+    fn parallel_in_order() {
+        let tasks = (0..8u64).collect::<Vec<_>>();
+        for &jobs in &[1, 3, 16] {
+            let mut results = Vec::new();
+            parallel(
+                jobs,
+                &tasks,
+                |&task| {
+                    thread::sleep(Duration::from_millis(8 - task));
+                    task * 2
+                },
+                |&task, result| results.push((task, result)),
+            );
+            assert_eq!(
+                results,
+                tasks.iter().map(|&t| (t, t * 2)).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn catch_results() {
+        assert_eq!(catch(|| 42), Ok(42));
+        assert_eq!(
+            catch(|| -> i32 { panic!("boom") }).unwrap_err().message,
+            "boom"
+        );
+    }
+
+    #[test]
+    fn cells() {
+        assert_eq!(cell("12345"), "12345");
+        assert_eq!(cell("\n##..\n..##"), "<multi-line>");
+        assert_eq!(cell("abcdefghijklmnopqrstuvwxyz"), "abcdefghijklmnopqrs…");
+    }
+}
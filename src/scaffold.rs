@@ -0,0 +1,184 @@
+use failure::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const SRC_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src");
+
+/// The line of a template that `--sample` points at the saved fixture.
+const SAMPLE_PLACEHOLDER: &str = "const SAMPLE_INPUT: &str = \"\";";
+
+/// The `Solution` type of a template, renamed after the new day.
+const SOLUTION_PLACEHOLDER: &str = "Day00";
+
+/// Creates `year{year}/day{NN}.rs` from the year's `day00.rs` template, which
+/// implements `Solution` for `Day00`, and registers its `DayNN` in the year's
+/// `mod.rs`. Existing days are never touched.
+pub fn new_day(src: &Path, year: i32, day: i32, sample: bool) -> Result<PathBuf, Error> {
+    let Plan {
+        path,
+        mod_rs,
+        contents,
+        registered,
+    } = plan(src, year, day, sample)?;
+
+    fs::write(&path, contents)?;
+    fs::write(&mod_rs, registered)?;
+    Ok(path)
+}
+
+/// Checks that a day can be created without touching existing work, so that
+/// nothing is fetched for a day that would be refused.
+pub fn check_new_day(src: &Path, year: i32, day: i32, sample: bool) -> Result<(), Error> {
+    plan(src, year, day, sample).map(|_| ())
+}
+
+/// What creating a day writes, worked out without writing anything.
+struct Plan {
+    path: PathBuf,
+    mod_rs: PathBuf,
+    contents: String,
+    registered: String,
+}
+
+fn plan(src: &Path, year: i32, day: i32, sample: bool) -> Result<Plan, Error> {
+    let dir = src.join(format!("year{}", year));
+    let template = dir.join("day00.rs");
+    let path = dir.join(format!("day{:02}.rs", day));
+    let mod_rs = dir.join("mod.rs");
+
+    if path.exists() {
+        return Err(ScaffoldError::Exists(path.display().to_string()).into());
+    }
+    let template_path = template.display().to_string();
+    let mut contents = fs::read_to_string(&template)
+        .map_err(|_| ScaffoldError::MissingTemplate(template_path.clone()))?;
+    if !contents.contains(SOLUTION_PLACEHOLDER) {
+        return Err(ScaffoldError::MissingSolution(template_path).into());
+    }
+    contents = contents.replace(SOLUTION_PLACEHOLDER, &format!("Day{:02}", day));
+    if sample {
+        if !contents.contains(SAMPLE_PLACEHOLDER) {
+            return Err(ScaffoldError::MissingPlaceholder(template_path).into());
+        }
+        contents = contents.replace(
+            SAMPLE_PLACEHOLDER,
+            &format!("const SAMPLE_INPUT: &str = sample!({}, {});", year, day),
+        );
+    }
+    let registered = register(&fs::read_to_string(&mod_rs)?, day)?;
+    Ok(Plan {
+        path,
+        mod_rs,
+        contents,
+        registered,
+    })
+}
+
+/// Inserts `dayNN::DayNN` into the sorted day list of a year's `solutions!`
+/// invocation.
+fn register(mod_rs: &str, day: i32) -> Result<String, Error> {
+    let entry = format!("day{:02}", day);
+    let mut lines = mod_rs.lines().map(str::to_owned).collect::<Vec<_>>();
+
+    let start = lines
+        .iter()
+        .position(|line| line.starts_with("solutions!("))
+        .ok_or(ScaffoldError::MissingSolutions)?;
+    let mut insert = start + 1;
+    for (i, line) in lines.iter().enumerate().skip(start + 1) {
+        let name = line.trim().trim_end_matches(',');
+        let module = name.split("::").next().unwrap_or("");
+        if !module.starts_with("day") {
+            break;
+        }
+        if module == entry {
+            return Err(ScaffoldError::Registered(entry).into());
+        }
+        if module < entry.as_str() {
+            insert = i + 1;
+        }
+    }
+
+    lines.insert(insert, format!("    {}::Day{:02},", entry, day));
+    Ok(lines.join("\n") + "\n")
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ScaffoldError {
+    #[error("{0} already exists")]
+    Exists(String),
+    #[error("no day template at {0}")]
+    MissingTemplate(String),
+    #[error("{0} does not implement `Solution` for `Day00`")]
+    MissingSolution(String),
+    #[error("{0} has no empty SAMPLE_INPUT constant to load the sample into")]
+    MissingPlaceholder(String),
+    #[error("no solutions! list in mod.rs")]
+    MissingSolutions,
+    #[error("{0} is already registered")]
+    Registered(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::tests::temp_dir;
+
+    const MOD_RS: &str = "solutions!(2023 {
+    day01,
+    day03,
+    day14::Day14,
+});
+";
+
+    #[test]
+    fn register_sorted() {
+        assert_eq!(
+            register(MOD_RS, 2).unwrap(),
+            "solutions!(2023 {
+    day01,
+    day02::Day02,
+    day03,
+    day14::Day14,
+});
+"
+        );
+        assert!(register(MOD_RS, 16)
+            .unwrap()
+            .contains("    day14::Day14,\n    day16::Day16,\n});"));
+        assert!(register(MOD_RS, 14).is_err());
+    }
+
+    #[test]
+    fn new_day_from_template() {
+        let src = temp_dir();
+        let dir = src.join("year2023");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("mod.rs"), MOD_RS).unwrap();
+        fs::write(
+            dir.join("day00.rs"),
+            "pub struct Day00;\nmod tests {\n    const SAMPLE_INPUT: &str = \"\";\n}\n",
+        )
+        .unwrap();
+
+        let path = new_day(&src, 2023, 5, true).unwrap();
+        assert_eq!(path, dir.join("day05.rs"));
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("pub struct Day05;"));
+        assert!(contents.contains("const SAMPLE_INPUT: &str = sample!(2023, 5);"));
+        assert!(fs::read_to_string(dir.join("mod.rs"))
+            .unwrap()
+            .contains("    day03,\n    day05::Day05,\n"));
+
+        assert!(new_day(&src, 2023, 5, false).is_err());
+
+        fs::write(dir.join("day00.rs"), "pub struct Day00;\n").unwrap();
+        assert!(check_new_day(&src, 2023, 6, false).is_ok());
+        assert!(new_day(&src, 2023, 6, true).is_err());
+        assert!(!dir.join("day06.rs").exists());
+
+        fs::write(dir.join("day00.rs"), "pub fn puzzle1() {}\n").unwrap();
+        assert!(check_new_day(&src, 2023, 6, false).is_err());
+        assert!(new_day(&src, 2022, 5, false).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use crate::answer::Answer;
+use std::collections::BTreeMap;
+
+/// A puzzle solution that parses its input once and then solves both parts
+/// from the parsed representation.
+pub trait Solution {
+    type Input;
+    type Part1: Into<Answer>;
+    type Part2: Into<Answer>;
+
+    fn parse(input: &str) -> Self::Input;
+    fn part1(input: &Self::Input) -> Self::Part1;
+    fn part2(input: &Self::Input) -> Self::Part2;
+}
+
+/// A parsed puzzle input with its solution type erased.
+pub trait Parsed {
+    fn part1(&self) -> Answer;
+    fn part2(&self) -> Answer;
+}
+
+pub type Parser = fn(&str) -> Box<dyn Parsed>;
+
+struct Typed<S: Solution>(S::Input);
+
+impl<S: Solution> Parsed for Typed<S> {
+    fn part1(&self) -> Answer {
+        S::part1(&self.0).into()
+    }
+
+    fn part2(&self) -> Answer {
+        S::part2(&self.0).into()
+    }
+}
+
+pub fn parse<S>(input: &str) -> Box<dyn Parsed>
+where
+    S: Solution + 'static,
+{
+    Box::new(Typed::<S>(S::parse(input)))
+}
+
+/// Adapter for days that only expose free `puzzle1` and `puzzle2` functions;
+/// parsing just keeps a copy of the raw input. Their answers are only known
+/// as text, so their kind is guessed from it.
+pub struct Puzzles {
+    pub input: String,
+    pub puzzle1: fn(&str) -> String,
+    pub puzzle2: fn(&str) -> String,
+}
+
+impl Parsed for Puzzles {
+    fn part1(&self) -> Answer {
+        Answer::new(&(self.puzzle1)(&self.input))
+    }
+
+    fn part2(&self) -> Answer {
+        Answer::new(&(self.puzzle2)(&self.input))
+    }
+}
+
+#[derive(Default)]
+pub struct Registry {
+    solutions: BTreeMap<(i32, i32), Parser>,
+}
+
+impl Registry {
+    pub fn new() -> Registry {
+        Registry::default()
+    }
+
+    pub fn insert(&mut self, year: i32, day: i32, parser: Parser) {
+        let previous = self.solutions.insert((year, day), parser);
+        assert!(
+            previous.is_none(),
+            "year {} day {} registered twice",
+            year,
+            day
+        );
+    }
+
+    pub fn get(&self, year: i32, day: i32) -> Option<Parser> {
+        self.solutions.get(&(year, day)).copied()
+    }
+
+    /// Returns all registered `(year, day)` pairs in ascending order.
+    pub fn days(&self) -> impl '_ + Iterator<Item = (i32, i32)> {
+        self.solutions.keys().copied()
+    }
+
+    pub fn years(&self) -> Vec<i32> {
+        let mut years = self.days().map(|(year, _)| year).collect::<Vec<_>>();
+        years.dedup();
+        years
+    }
+}
+
+/// Parses the day number out of a `dayNN` module name.
+pub fn day_number(module: &str) -> i32 {
+    module
+        .trim_start_matches("day")
+        .parse()
+        .unwrap_or_else(|_| panic!("invalid day module name {}", module))
+}
+
+/// Declares the day modules of a year and a `register` function adding them
+/// to a `Registry`. Days listed as `dayNN` are expected to expose free
+/// `puzzle1` and `puzzle2` functions, while `dayNN::Type` registers a
+/// `Solution` implementation.
+macro_rules! solutions {
+    ($year:literal { $($day:ident $(:: $solution:ident)?,)* }) => {
+        $(pub mod $day;)*
+
+        pub fn register(registry: &mut $crate::solution::Registry) {
+            $(
+                registry.insert(
+                    $year,
+                    $crate::solution::day_number(stringify!($day)),
+                    solutions!(@parser $day $(:: $solution)?),
+                );
+            )*
+        }
+    };
+    (@parser $day:ident) => {
+        |input| {
+            Box::new($crate::solution::Puzzles {
+                input: input.to_owned(),
+                puzzle1: |input| $day::puzzle1(input).to_string(),
+                puzzle2: |input| $day::puzzle2(input).to_string(),
+            })
+        }
+    };
+    (@parser $day:ident :: $solution:ident) => {
+        $crate::solution::parse::<$day::$solution>
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Art;
+
+    struct Sum;
+
+    impl Solution for Sum {
+        type Input = Vec<i64>;
+        type Part1 = i64;
+        type Part2 = i64;
+
+        fn parse(input: &str) -> Vec<i64> {
+            crate::util::parse(input).collect()
+        }
+
+        fn part1(input: &Vec<i64>) -> i64 {
+            input.iter().sum()
+        }
+
+        fn part2(input: &Vec<i64>) -> i64 {
+            input.iter().product()
+        }
+    }
+
+    #[test]
+    fn typed_solution() {
+        let parsed = parse::<Sum>("2\n3\n4");
+        assert_eq!(parsed.part1(), Answer::Integer(9));
+        assert_eq!(parsed.part2(), Answer::Integer(24));
+    }
+
+    struct Row;
+
+    impl Solution for Row {
+        type Input = usize;
+        type Part1 = String;
+        type Part2 = Art;
+
+        fn parse(input: &str) -> usize {
+            input.trim().len()
+        }
+
+        fn part1(len: &usize) -> String {
+            "#".repeat(*len)
+        }
+
+        fn part2(len: &usize) -> Art {
+            Art("#".repeat(*len))
+        }
+    }
+
+    #[test]
+    fn typed_art() {
+        let parsed = parse::<Row>("abc");
+        assert_eq!(parsed.part1(), Answer::Text("###".to_string()));
+        assert_eq!(parsed.part2(), Answer::Art(vec!["###".to_string()]));
+    }
+
+    #[test]
+    fn registry_days() {
+        let mut registry = Registry::new();
+        registry.insert(2023, 2, parse::<Sum>);
+        registry.insert(2022, 1, parse::<Sum>);
+        registry.insert(2023, 1, parse::<Sum>);
+
+        assert_eq!(
+            registry.days().collect::<Vec<_>>(),
+            vec![(2022, 1), (2023, 1), (2023, 2)]
+        );
+        assert_eq!(registry.years(), vec![2022, 2023]);
+        assert!(registry.get(2023, 2).is_some());
+        assert!(registry.get(2023, 3).is_none());
+    }
+
+    #[test]
+    fn day_numbers() {
+        assert_eq!(day_number("day01"), 1);
+        assert_eq!(day_number("day25"), 25);
+    }
+
+    #[test]
+    fn registered_solutions() {
+        let registry = crate::registry();
+        assert!(registry.get(2023, 14).is_some());
+        assert!(registry.get(2016, 10).is_some());
+    }
+}
@@ -0,0 +1,177 @@
+use crate::calendar;
+use crate::runner::{Failure, Outcome, Part, Run};
+use colored::Colorize;
+use std::collections::BTreeMap;
+
+/// How far along the solution for a day is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Status {
+    Missing,
+    /// Registered, but there was no cached input to check it against, or it
+    /// failed or timed out on it.
+    Registered,
+    Stubbed,
+    Part1,
+    Solved,
+}
+
+impl Status {
+    /// Classifies a registered day from a run, where answers of 0 and
+    /// `todo!()` or `unimplemented!()` panics are taken to be stubs. A part
+    /// that timed out may or may not be implemented, so it leaves the day
+    /// unchecked.
+    pub fn of(run: &Run) -> Status {
+        match &run.outcome {
+            Outcome::Solved(part1, part2) => {
+                let stub = |part: &Part| match part {
+                    Ok(answer) => answer.is_stub(),
+                    Err(Failure::Panicked(panic)) => {
+                        panic.message.starts_with("not implemented")
+                            || panic.message.starts_with("not yet implemented")
+                    }
+                    Err(_) => false,
+                };
+                let timed_out =
+                    |part: &Part| matches!(part, Err(Failure::TimedOut(_)) | Err(Failure::Skipped));
+                if stub(part1) {
+                    Status::Stubbed
+                } else if timed_out(part1) || timed_out(part2) {
+                    Status::Registered
+                } else if stub(part2) {
+                    Status::Part1
+                } else {
+                    Status::Solved
+                }
+            }
+            _ => Status::Registered,
+        }
+    }
+
+    fn marker(self, color: bool) -> String {
+        let marker = match self {
+            Status::Missing => ".",
+            Status::Registered => "?",
+            Status::Stubbed => "0",
+            Status::Part1 => "+",
+            Status::Solved => "*",
+        };
+        if !color {
+            return marker.to_string();
+        }
+        match self {
+            Status::Missing => marker.bright_black(),
+            Status::Registered => marker.normal(),
+            Status::Stubbed => marker.red(),
+            Status::Part1 => marker.white(),
+            Status::Solved => marker.yellow(),
+        }
+        .to_string()
+    }
+}
+
+/// Renders a row of day markers per year, followed by a legend.
+pub fn render(years: &[i32], statuses: &BTreeMap<(i32, i32), Status>, color: bool) -> String {
+    let days = years
+        .iter()
+        .map(|&year| calendar::days_in_event(year))
+        .max()
+        .unwrap_or(25);
+    let tens = (1..=days)
+        .map(|day| match day / 10 {
+            0 => ' ',
+            tens => std::char::from_digit(tens as u32, 10).expect("single digit"),
+        })
+        .collect::<String>();
+    let units = (1..=days)
+        .map(|day| std::char::from_digit((day % 10) as u32, 10).expect("single digit"))
+        .collect::<String>();
+
+    let mut lines = vec![
+        format!("      {}", tens).trim_end().to_string(),
+        format!("      {}", units),
+    ];
+    for &year in years {
+        let row = (1..=calendar::days_in_event(year))
+            .map(|day| {
+                let status = statuses.get(&(year, day)).copied();
+                status.unwrap_or(Status::Missing).marker(color)
+            })
+            .collect::<String>();
+        lines.push(format!("{}  {}", year, row));
+    }
+    lines.push(String::new());
+    lines.push(format!(
+        "{} solved  {} part 1 only  {} stubbed  {} registered, unchecked  {} missing",
+        Status::Solved.marker(color),
+        Status::Part1.marker(color),
+        Status::Stubbed.marker(color),
+        Status::Registered.marker(color),
+        Status::Missing.marker(color)
+    ));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+    use crate::runner::Panic;
+    use std::time::Duration;
+
+    fn run(part1: &str, part2: &str) -> Run {
+        Run {
+            year: 2016,
+            day: 8,
+            outcome: Outcome::Solved(Ok(Answer::new(part1)), Ok(Answer::new(part2))),
+            times: [Duration::default(); 3],
+        }
+    }
+
+    #[test]
+    fn classify() {
+        assert_eq!(Status::of(&run("110", "ZJHRKCPLYJ")), Status::Solved);
+        assert_eq!(Status::of(&run("110", "0")), Status::Part1);
+        assert_eq!(Status::of(&run("0", "0")), Status::Stubbed);
+        assert_eq!(
+            Status::of(&Run::failed(2016, 8, "no input".to_string())),
+            Status::Registered
+        );
+
+        let mut timed_out = run("110", "0");
+        timed_out.outcome = Outcome::Solved(
+            Ok(Answer::new("110")),
+            Err(Failure::TimedOut(Duration::from_secs(1))),
+        );
+        assert_eq!(Status::of(&timed_out), Status::Registered);
+        timed_out.outcome = Outcome::Solved(Err(Failure::Skipped), Err(Failure::Skipped));
+        assert_eq!(Status::of(&timed_out), Status::Registered);
+
+        let mut todo = run("110", "0");
+        todo.outcome = Outcome::Solved(
+            Ok(Answer::new("110")),
+            Err(Failure::Panicked(Panic {
+                message: "not yet implemented".to_string(),
+                location: None,
+            })),
+        );
+        assert_eq!(Status::of(&todo), Status::Part1);
+    }
+
+    #[test]
+    fn render_grid() {
+        let mut statuses = BTreeMap::new();
+        statuses.insert((2016, 1), Status::Solved);
+        statuses.insert((2016, 8), Status::Part1);
+        statuses.insert((2016, 10), Status::Stubbed);
+        statuses.insert((2025, 12), Status::Registered);
+        assert_eq!(
+            render(&[2016, 2025], &statuses, false),
+            "               1111111111222222
+      1234567890123456789012345
+2016  *......+.0...............
+2025  ...........?
+
+* solved  + part 1 only  0 stubbed  ? registered, unchecked  . missing"
+        );
+    }
+}
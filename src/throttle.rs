@@ -0,0 +1,164 @@
+use chrono::{DateTime, Utc};
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Delay before the first retry, doubling with each further attempt.
+const BACKOFF_BASE: Duration = Duration::from_secs(2);
+
+/// Tells the time and waits, so that tests can do both without sleeping.
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> SystemTime;
+    fn sleep(&self, duration: Duration);
+}
+
+#[derive(Debug)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// Spaces out requests by a minimum interval, shared by all clones of a
+/// client and all threads using it.
+#[derive(Debug)]
+pub struct Throttle {
+    clock: Arc<dyn Clock>,
+    interval: Duration,
+    last: Mutex<Option<SystemTime>>,
+}
+
+impl Throttle {
+    pub fn new(clock: Arc<dyn Clock>, interval: Duration) -> Throttle {
+        Throttle {
+            clock,
+            interval,
+            last: Mutex::new(None),
+        }
+    }
+
+    pub fn clock(&self) -> &dyn Clock {
+        &*self.clock
+    }
+
+    /// Waits until the interval has passed since the previous request.
+    pub fn wait(&self) {
+        let mut last = self.last.lock().expect("throttle poisoned");
+        if let Some(last) = *last {
+            let elapsed = self.clock.now().duration_since(last).unwrap_or_default();
+            if elapsed < self.interval {
+                self.clock.sleep(self.interval - elapsed);
+            }
+        }
+        *last = Some(self.clock.now());
+    }
+}
+
+pub fn backoff(attempt: u32) -> Duration {
+    BACKOFF_BASE * 2u32.pow(attempt)
+}
+
+/// Parses a `Retry-After` value given either in seconds or as a date.
+pub fn retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    if let Ok(secs) = value.trim().parse() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    Some(
+        SystemTime::from(date)
+            .duration_since(now)
+            .unwrap_or_default(),
+    )
+}
+
+/// Formats a time as an HTTP date, as used by `If-Modified-Since`.
+pub fn http_date(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time)
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// A clock that only moves when slept on, recording each sleep.
+    #[derive(Debug)]
+    pub struct FakeClock {
+        now: Mutex<SystemTime>,
+        sleeps: Mutex<Vec<Duration>>,
+    }
+
+    impl FakeClock {
+        pub fn new() -> Arc<FakeClock> {
+            Arc::new(FakeClock {
+                now: Mutex::new(SystemTime::now()),
+                sleeps: Mutex::default(),
+            })
+        }
+
+        pub fn advance(&self, duration: Duration) {
+            *self.now.lock().unwrap() += duration;
+        }
+
+        pub fn sleeps(&self) -> Vec<Duration> {
+            self.sleeps.lock().unwrap().clone()
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> SystemTime {
+            *self.now.lock().unwrap()
+        }
+
+        fn sleep(&self, duration: Duration) {
+            self.sleeps.lock().unwrap().push(duration);
+            self.advance(duration);
+        }
+    }
+
+    #[test]
+    fn throttle() {
+        let clock = FakeClock::new();
+        let throttle = Throttle::new(clock.clone(), Duration::from_secs(1));
+        throttle.wait();
+        assert!(clock.sleeps().is_empty());
+
+        clock.advance(Duration::from_millis(300));
+        throttle.wait();
+        assert_eq!(clock.sleeps(), vec![Duration::from_millis(700)]);
+
+        clock.advance(Duration::from_secs(5));
+        throttle.wait();
+        assert_eq!(clock.sleeps().len(), 1);
+    }
+
+    #[test]
+    fn backoffs() {
+        let delays = (0..4).map(backoff).collect::<Vec<_>>();
+        assert_eq!(delays, [2, 4, 8, 16].map(Duration::from_secs));
+    }
+
+    #[test]
+    fn retry_after_values() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(784111777);
+        assert_eq!(http_date(now), "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(retry_after("120", now), Some(Duration::from_secs(120)));
+        assert_eq!(
+            retry_after("Sun, 06 Nov 1994 08:50:07 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            retry_after("Sun, 06 Nov 1994 08:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(retry_after("soon", now), None);
+    }
+}
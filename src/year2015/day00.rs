@@ -1,24 +1,37 @@
-fn parse_input(input: &str) {}
+use crate::solution::Solution;
 
-pub fn puzzle1(input: &str) -> u32 {
-    0
-}
+pub struct Day00;
+
+impl Solution for Day00 {
+    type Input = ();
+    type Part1 = u32;
+    type Part2 = u32;
 
-pub fn puzzle2(input: &str) -> u32 {
-    0
+    fn parse(_input: &str) {}
+
+    fn part1(_input: &()) -> u32 {
+        0
+    }
+
+    fn part2(_input: &()) -> u32 {
+        0
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::Day00;
+    use crate::solution::Solution;
+
     const SAMPLE_INPUT: &str = "";
 
     #[test]
     fn puzzle1() {
-        assert_eq!(super::puzzle1(SAMPLE_INPUT), 0);
+        assert_eq!(Day00::part1(&Day00::parse(SAMPLE_INPUT)), 0);
     }
 
     #[test]
     fn puzzle2() {
-        assert_eq!(super::puzzle2(SAMPLE_INPUT), 0);
+        assert_eq!(Day00::part2(&Day00::parse(SAMPLE_INPUT)), 0);
     }
 }
@@ -1,5 +1,7 @@
-pub mod day01;
-pub mod day02;
-pub mod day03;
-pub mod day04;
-pub mod day05;
+solutions!(2019 {
+    day01,
+    day02,
+    day03,
+    day04,
+    day05,
+});
@@ -1,20 +1,37 @@
-pub fn puzzle1(input: &str) -> i64 {
-    0
-}
+use crate::solution::Solution;
+
+pub struct Day00;
+
+impl Solution for Day00 {
+    type Input = ();
+    type Part1 = i64;
+    type Part2 = i64;
 
-pub fn puzzle2(input: &str) -> i64 {
-    0
+    fn parse(_input: &str) {}
+
+    fn part1(_input: &()) -> i64 {
+        0
+    }
+
+    fn part2(_input: &()) -> i64 {
+        0
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::Day00;
+    use crate::solution::Solution;
+
+    const SAMPLE_INPUT: &str = "";
+
     #[test]
     fn puzzle1() {
-        assert_eq!(super::puzzle1(""), 0);
+        assert_eq!(Day00::part1(&Day00::parse(SAMPLE_INPUT)), 0);
     }
 
     #[test]
     fn puzzle2() {
-        assert_eq!(super::puzzle2(""), 0);
+        assert_eq!(Day00::part2(&Day00::parse(SAMPLE_INPUT)), 0);
     }
 }
@@ -1,3 +1,5 @@
-pub mod day01;
-pub mod day02;
-pub mod day03;
+solutions!(2020 {
+    day01,
+    day02,
+    day03,
+});
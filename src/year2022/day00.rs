@@ -1,21 +1,37 @@
+use crate::solution::Solution;
 
-pub fn puzzle1(_input: &str) -> i64 {
-    0
-}
+pub struct Day00;
+
+impl Solution for Day00 {
+    type Input = ();
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(_input: &str) {}
+
+    fn part1(_input: &()) -> i64 {
+        0
+    }
 
-pub fn puzzle2(_input: &str) -> i64 {
-    0
+    fn part2(_input: &()) -> i64 {
+        0
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::Day00;
+    use crate::solution::Solution;
+
+    const SAMPLE_INPUT: &str = "";
+
     #[test]
     fn puzzle1() {
-        assert_eq!(super::puzzle1(""), 0);
+        assert_eq!(Day00::part1(&Day00::parse(SAMPLE_INPUT)), 0);
     }
 
     #[test]
     fn puzzle2() {
-        assert_eq!(super::puzzle2(""), 0);
+        assert_eq!(Day00::part2(&Day00::parse(SAMPLE_INPUT)), 0);
     }
 }
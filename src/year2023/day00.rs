@@ -1,29 +1,37 @@
-pub fn parse_input(input: &str) {
-    println!("Got input of length {}", input.len());
-}
+use crate::solution::Solution;
 
-pub fn puzzle1(input: &str) -> u32 {
-    parse_input(input);
-    0
-}
+pub struct Day00;
+
+impl Solution for Day00 {
+    type Input = ();
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(_input: &str) {}
 
-pub fn puzzle2(input: &str) -> u32 {
-    parse_input(input);
-    0
+    fn part1(_input: &()) -> u32 {
+        0
+    }
+
+    fn part2(_input: &()) -> u32 {
+        0
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::Day00;
+    use crate::solution::Solution;
 
     const SAMPLE_INPUT: &str = "";
 
     #[test]
     fn puzzle1() {
-        assert_eq!(super::puzzle1(SAMPLE_INPUT), 0);
+        assert_eq!(Day00::part1(&Day00::parse(SAMPLE_INPUT)), 0);
     }
 
     #[test]
     fn puzzle2() {
-        assert_eq!(super::puzzle2(SAMPLE_INPUT), 0);
+        assert_eq!(Day00::part2(&Day00::parse(SAMPLE_INPUT)), 0);
     }
 }
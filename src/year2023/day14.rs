@@ -1,3 +1,5 @@
+use crate::solution::Solution;
+
 enum Direction {
     North,
     West,
@@ -5,7 +7,8 @@ enum Direction {
     East,
 }
 
-struct Platform {
+#[derive(Clone)]
+pub struct Platform {
     grid: Vec<Vec<char>>,
 }
 
@@ -110,43 +113,49 @@ impl Platform {
     }
 }
 
-pub fn puzzle1(input: &str) -> usize {
-    let mut platform = Platform::from(input);
-    platform.tilt(Direction::North);
+pub struct Day14;
 
-    platform.load()
-}
+impl Solution for Day14 {
+    type Input = Platform;
+    type Part1 = usize;
+    type Part2 = usize;
 
-pub fn puzzle2(input: &str) -> usize {
-    let mut platform = Platform::from(input);
+    fn parse(input: &str) -> Platform {
+        Platform::from(input)
+    }
 
-    for _ in 0..1000 {
-        platform.cycle();
+    fn part1(platform: &Platform) -> usize {
+        let mut platform = platform.clone();
+        platform.tilt(Direction::North);
+
+        platform.load()
     }
 
-    platform.load()
+    fn part2(platform: &Platform) -> usize {
+        let mut platform = platform.clone();
+
+        for _ in 0..1000 {
+            platform.cycle();
+        }
+
+        platform.load()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    const SAMPLE_INPUT: &str = "O....#....
-O.OO#....#
-.....##...
-OO.#O....O
-.O.....O#.
-O.#..O.#.#
-..O..#O..O
-.......O..
-#....###..
-#OO..#....";
+    use super::Day14;
+    use crate::solution::Solution;
+
+    const SAMPLE_INPUT: &str = sample!(2023, 14);
 
     #[test]
     fn puzzle1() {
-        assert_eq!(super::puzzle1(SAMPLE_INPUT), 136);
+        assert_eq!(Day14::part1(&Day14::parse(SAMPLE_INPUT)), 136);
     }
 
     #[test]
     fn puzzle2() {
-        assert_eq!(super::puzzle2(SAMPLE_INPUT), 64);
+        assert_eq!(Day14::part2(&Day14::parse(SAMPLE_INPUT)), 64);
     }
 }